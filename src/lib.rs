@@ -3,7 +3,7 @@
 //!
 //!  expect input for the macro to look like:
 //!
-//! ```
+//! ```ignore
 //! wasmcloud_provider_macros::generate!(YourProvider, ...wit-bindgen args)
 //!
 //! struct YourProvider;
@@ -14,508 +14,3146 @@
 //!   ...
 //! }
 //!
-//! // Implementation of your exported wasmcloud interface
+//! // Implementation of your exported wasmcloud interface (if the world exports any), wired up
+//! // by the `export_<world>!` macro that wit-bindgen generates
 //! impl crate::exports::wasmcloud::some_contract::some_interface::SomeFunction for YourProvider {
 //!     ...
 //! }
 //!
+//! export_your_world!(YourProvider);
+//!
+//! // Check that the provider implements everything a wasmCloud provider requires
 //! export_contract!(YourProvider);
 //! ```
+//!
+//! The operations handled by the provider are enumerated by the generated `Operation` enum (with a variant per
+//! method, ex. `Operation::Get`), which parses from and converts to the method names received over the lattice, for
+//! use in routing tables, metrics or access control (it is not generated with `dispatch: false`). The names of every
+//! operation are also available at runtime as the provider's `SUPPORTED_OPERATIONS` associated const (ex.
+//! `YourProvider::SUPPORTED_OPERATIONS`, as the SDK has no hook for reporting them to the host). An associated item
+//! of the same name defined by the provider clashes with it, which is reported at the provider in `generate!`.
+//!
+//! Each method of the exported interfaces must be implemented as an inherent `async fn` of the provider taking
+//! `ctx: wasmcloud_provider_sdk::Context` followed by the WIT function's arguments. A missing method is reported as a
+//! compile error pointing at the provider in `generate!`, and the expected signatures can be generated with the
+//! `method_skeleton` option. WIT functions returning a `result` are implemented returning a `Result`, whose error is
+//! stringified when sent over the lattice, while the value of other (infallible) functions is sent as-is. A `result`
+//! without an ok type (ex. `result<_, string>`) is a `Result<(), E>`, and one without an error type (ex. bare
+//! `result`) is a `Result<T, ()>`, whose error is reported as the method having failed.
+//!
+//! Health checks from the host are answered by `_health_request()`, which providers may optionally define
+//! alongside their other methods (if not defined, the provider always reports itself as healthy):
+//!
+//! ```ignore
+//! impl YourProvider {
+//!     async fn _health_request(
+//!         &self,
+//!         arg: &wasmcloud_provider_sdk::core::HealthCheckRequest,
+//!     ) -> wasmcloud_provider_sdk::core::HealthCheckResponse {
+//!         ...
+//!     }
+//! }
+//! ```
+//!
+//! Generic providers are supported by listing the provider's generic params (with their bounds and any where clause)
+//! in `generate!`, ex. `generate!(YourProvider<B: Backend>, ...)` or `generate!(YourProvider<B> where B: Backend, ...)`,
+//! which are then used for every generated impl.
+//!
+//! A single world is generated per invocation (as with wit-bindgen), so a provider handling the contracts of several
+//! worlds should use a world that imports all of their interfaces, which may belong to different packages and namespaces.
+//! A world that imports nothing (ex. while scaffolding a provider) produces a provider that handles no methods,
+//! rejecting every invocation.
+//!
+//! Logic that should run around every invocation (ex. rate limiting, metrics) can be added with dispatch hooks,
+//! which providers may optionally define alongside their other methods. `_before_dispatch()` runs before the
+//! invocation is decoded, rejecting it when returning an error, and `_after_dispatch()` receives the response.
+//! Both receive the method name as it was received (after any `method_rewrite`), including for methods the provider
+//! does not handle, whose invocations are rejected after `_before_dispatch()` (the name of a handled method can be
+//! turned into an `Operation` with `method.parse::<Operation>()`):
+//!
+//! ```ignore
+//! impl YourProvider {
+//!     async fn _before_dispatch(
+//!         &self,
+//!         ctx: &wasmcloud_provider_sdk::Context,
+//!         method: &str,
+//!     ) -> Result<(), wasmcloud_provider_sdk::error::ProviderInvocationError> {
+//!         ...
+//!     }
+//!
+//!     async fn _after_dispatch(
+//!         &self,
+//!         ctx: &wasmcloud_provider_sdk::Context,
+//!         method: &str,
+//!         result: &Result<Vec<u8>, wasmcloud_provider_sdk::error::ProviderInvocationError>,
+//!     ) {
+//!         ...
+//!     }
+//! }
+//! ```
+//!
+//! The wit-bindgen options that are supported are those of the version of wit-bindgen in use (0.9): `world`, `path`,
+//! `inline`, `ownership`, `skip`, `std_feature`, `raw_strings`, `macro_export`, `macro_call_prefix`,
+//! `export_macro_name` and `runtime_path`. Options of later versions (ex. `with`) are rejected, so interfaces cannot
+//! be remapped to types generated elsewhere. WIT resources cannot be generated by that version either, and are
+//! reported as a compile error naming the resource. Neither can WIT functions returning a `stream`, so responses are
+//! always sent as a single message.
+//!
+//! When wit-bindgen args are supplied in braced form, wasmCloud-specific options may be mixed in with them
+//! (ex. `{ world: "provider", path: "wit", trait_style: "trait_variant" }`), which control how the provider is
+//! generated (ex. the wire format, the naming of generated items, or the paths of the crates it uses). Each option
+//! is documented where it is declared, on the variants of `ProviderOpt` (in `src/opts.rs`).
+//!
+//! When the provider is built with its `otel` feature enabled, `dispatch` runs in a [`tracing`](https://docs.rs/tracing)
+//! span recording the method, the sending actor and the interface, which continues the trace propagated in the
+//! invocation's `Context` (requires `tracing`, `tracing-opentelemetry` and `opentelemetry` as dependencies of the provider).
+//!
+//! To diagnose unexpected output, the macro can log its traversal of the wit-bindgen output (indented by module level,
+//! with the module path) along with the generated source to stderr, either by building it with its `debug` feature or
+//! by setting `WASMCLOUD_MACRO_DEBUG=1` in the environment of the build. Cargo does not track the variable, so
+//! setting it does not re-expand a provider that was already built: rebuild it (or `touch` the file invoking
+//! `generate!`) for the output to be produced.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use heck::ToUpperCamelCase;
-use proc_macro2::{Ident, Punct, Spacing, Span, TokenTree};
+use heck::{ToKebabCase, ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+use proc_macro2::{Delimiter, Ident, Punct, Spacing, Span, TokenTree};
+mod opts;
 mod vendor;
 use quote::{format_ident, ToTokens, TokenStreamExt};
 use syn::{
-    punctuated::Punctuated, token::PathSep, visit_mut::visit_item_mut, visit_mut::VisitMut,
+    ext::IdentExt, punctuated::Punctuated, spanned::Spanned, token::PathSep, visit_mut::VisitMut,
     AttrStyle, Attribute, Item, ItemFn, ItemMod, LitStr, Meta, MetaList, Path, PathSegment,
     ReturnType, Token,
 };
 
-use vendor::wit_bindgen_rust_macro::generate as wit_bindgen_generate;
+use opts::{
+    split_top_level_commas, ExportContractArgs, LatticeMethodStyle, MethodMode, ProviderOpts,
+    StructNaming, TraitStyle, WireFormat,
+};
+use vendor::wit_bindgen_rust_macro::generate_with_world_info as wit_bindgen_generate;
+
+/// Print a line of debug output, when enabled (see [`debug_enabled`])
+///
+/// The arguments are only formatted when debug output is enabled, so this is a no-op otherwise.
+macro_rules! debug_print {
+    ($($arg:tt)*) => {
+        if debug_enabled() {
+            eprintln!("DEBUG: {}", format_args!($($arg)*));
+        }
+    };
+}
 
 type WitNamespaceName = String;
 type WitPackageName = String;
 type WitInterfaceName = String;
+type WitPackageVersion = String;
+
+/// A WIT interface, qualified by the namespace and package it belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct WitInterfacePath {
+    ns: WitNamespaceName,
+    package: WitPackageName,
+    version: Option<WitPackageVersion>,
+    iface: WitInterfaceName,
+}
 
 /// Error message shown when the macro receives invalid args
-const INVALID_INPUT_ERROR_TEXT: &str = r#"
+const INVALID_INPUT_ERROR_TEXT: &str = r#"expected a provider struct name followed by wit-bindgen args, ex.:
+
+    generate!(YourProvider, "your-world");
+    generate!(YourProvider, "your-world" in "path/to/wit");
+    generate!(YourProvider, { world: "your-world", path: "path/to/wit" });
+    generate!(crate::providers::YourProvider, "your-world");
+"#;
+
+/// Error message shown when wit-bindgen generates no code
+const EMPTY_BINDGEN_OUTPUT_ERROR_TEXT: &str =
+    "wit-bindgen produced no output; check your world/package args";
 
+/// Error message shown when the WIT uses the legacy `default world` syntax
+const LEGACY_DEFAULT_WORLD_ERROR_TEXT: &str = r#"failed to parse WIT: `default world` is legacy syntax that is no longer supported
+
+Remove `default` from the world declaration (ex. `default world keyvalue { ... }` -> `world keyvalue { ... }`),
+and select the world by name in the macro args if the package contains more than one world.
 "#;
 
+/// Error message shown (after the error reported by wit-bindgen) when the WIT contains a recursive type
+const RECURSIVE_TYPE_ERROR_TEXT: &str = r#"WIT types cannot refer to themselves (directly or through other types), so a
+record like `record node { children: list<node> }` can't be received over the lattice either.
+
+Store recursive structures flattened instead (ex. `record tree { nodes: list<node> }` with
+`record node { name: string, children: list<u32> }`, where children are indices into `nodes`)."#;
+
 /// Performs procedural macro generation, utilizing [`wit-bindgen`](https://github.com/bytecodealliance/wit-bindgen), and making
 /// changes to it's output.
 ///
 /// This macro generates functionality necessary to use a WIT-enabled Rust providers (a [`wasmtime::component`])
 #[proc_macro]
 pub fn generate(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let item: proc_macro2::TokenStream = input.into();
+    let expanded = expand(input.into());
+    debug_print!(
+        "generated provider source:\n{}",
+        render_expansion(&expanded)
+    );
+    expanded.into()
+}
+
+/// Completes a provider that was generated with [`generate!`], taking the provider struct as its argument
+///
+/// This macro ensures that the provider implements everything a wasmCloud provider requires (i.e. the generated
+/// `ProviderHandler` and `MessageDispatch` impls, which rely on methods like `_put_link()`), producing a compile error
+/// pointing at the provider otherwise. Providers generated with `sdk_path` must pass the same path here
+/// (ex. `export_contract!(YourProvider, sdk_path: crate::deps::sdk)`).
+///
+/// It does not wire up the interfaces exported by the world, which the version of wit-bindgen in use (0.9) does
+/// with the `export_<world>!` macro it generates for worlds with exports (see its `export_macro_name` option),
+/// ex. `export_keyvalue!(YourProvider)`.
+#[proc_macro]
+pub fn export_contract(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ExportContractArgs { provider, sdk_path } = match syn::parse(input) {
+        Ok(args) => args,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let sdk_path = sdk_path.unwrap_or_else(|| syn::parse_quote!(::wasmcloud_provider_sdk));
+
+    quote::quote_spanned!(provider.span()=>
+        const _: () = {
+            fn assert_provider<P: #sdk_path::Provider>() {}
+            let _ = assert_provider::<#provider>;
+        };
+    )
+    .into()
+}
+
+/// Render the output of [`expand`] as formatted Rust source
+///
+/// This is primarily useful for snapshotting the code generated for a given WIT world,
+/// in order to catch unintended changes to codegen. Without the `debug` feature (i.e. when debug
+/// output was enabled with `WASMCLOUD_MACRO_DEBUG`), items are rendered unformatted, one per line.
+fn render_expansion(ts: &proc_macro2::TokenStream) -> String {
+    let Ok(file) = syn::parse2::<syn::File>(ts.clone()) else {
+        return ts.to_string();
+    };
+
+    #[cfg(any(feature = "debug", test))]
+    return prettyplease::unparse(&file);
 
+    #[cfg(not(any(feature = "debug", test)))]
+    {
+        let mut rendered = String::new();
+        render_items(&file.attrs, &file.items, 0, &mut rendered);
+        rendered
+    }
+}
+
+/// Render (inner) attributes & items unformatted, one per line, with the items of inline modules indented
+#[cfg_attr(any(feature = "debug", test), allow(dead_code))]
+fn render_items(attrs: &[Attribute], items: &[Item], depth: usize, rendered: &mut String) {
+    let indent = "    ".repeat(depth);
+    for attr in attrs
+        .iter()
+        .filter(|a| matches!(a.style, AttrStyle::Inner(_)))
+    {
+        rendered.push_str(&format!("{indent}{}\n", attr.to_token_stream()));
+    }
+    for item in items {
+        match item {
+            Item::Mod(ItemMod {
+                attrs,
+                vis,
+                unsafety,
+                ident,
+                content: Some((_, items)),
+                ..
+            }) => {
+                for attr in attrs.iter().filter(|a| matches!(a.style, AttrStyle::Outer)) {
+                    rendered.push_str(&format!("{indent}{}\n", attr.to_token_stream()));
+                }
+                let vis = vis.to_token_stream().to_string();
+                let unsafety = unsafety.map(|_| "unsafe ").unwrap_or_default();
+                let vis = if vis.is_empty() {
+                    vis
+                } else {
+                    format!("{vis} ")
+                };
+                rendered.push_str(&format!("{indent}{vis}{unsafety}mod {ident} {{\n"));
+                render_items(attrs, items, depth + 1, rendered);
+                rendered.push_str(&format!("{indent}}}\n"));
+            }
+            item => rendered.push_str(&format!("{indent}{}\n", item.to_token_stream())),
+        }
+    }
+}
+
+/// Expand the input to the `generate!` macro into the final provider code
+fn expand(item: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
     // Ensure that we have the args we expect (at least 5 tokens should be present):
     // (<impl struct name> <comma> <... wit-bindgen args>)
     let tokens = item.into_iter().collect::<Vec<TokenTree>>();
     if tokens.len() < 3 {
-        panic!("invalid token length, {}", INVALID_INPUT_ERROR_TEXT);
+        let span = tokens.first().map_or_else(Span::call_site, TokenTree::span);
+        return syn::Error::new(
+            span,
+            format!("missing arguments to macro, {INVALID_INPUT_ERROR_TEXT}"),
+        )
+        .to_compile_error();
     }
 
-    // Extract the identifier for the impl struct name from the tokens supplied
-    let (impl_struct_name, rest) = match tokens.split_at(2) {
-        (&[TokenTree::Ident(ref struct_name), TokenTree::Punct(ref p)], rest)
-            if p.as_char() == ',' =>
-        {
-            (struct_name, rest)
-        }
-        _ => {
-            panic!(
-                "missing/invalid arguments to macro, {}",
-                INVALID_INPUT_ERROR_TEXT
-            );
-        }
+    // Extract the path to the impl struct (ex. `YourProvider` or `crate::providers::YourProvider`)
+    // and its generic params (ex. `YourProvider<B: Backend>`) from the tokens supplied before the first comma
+    let Some(comma_idx) = find_provider_arg_end(&tokens) else {
+        return syn::Error::new(
+            tokens[0].span(),
+            format!("invalid arguments to macro, {INVALID_INPUT_ERROR_TEXT}"),
+        )
+        .to_compile_error();
     };
+    let (struct_path, rest) = (&tokens[..comma_idx], &tokens[comma_idx + 1..]);
+    let (impl_struct_name, provider_generics) =
+        match syn::parse::Parser::parse2(parse_provider_arg, struct_path.iter().cloned().collect())
+        {
+            Ok(v) => v,
+            Err(e) => {
+                return syn::Error::new(
+                    e.span(),
+                    format!("invalid arguments to macro, {INVALID_INPUT_ERROR_TEXT}"),
+                )
+                .to_compile_error();
+            }
+        };
+
+    // Catch stray punctuation before it reaches wit-bindgen, which would produce a confusing error
+    if let Err(e) = validate_bindgen_args(rest) {
+        return e.to_compile_error();
+    }
 
-    // // Seperate the wit bindgen args
+    // // Seperate the wit bindgen args, dropping any trailing comma
+    let rest = match rest {
+        [args @ .., TokenTree::Punct(p)] if p.as_char() == ',' => args,
+        args => args,
+    };
     let mut bindgen_args = proc_macro2::TokenStream::new();
     bindgen_args.extend(Vec::from(rest));
 
-    // Perform wit-bindgen on the tokens that are bindgen args
-    let wit_bindgen_ts: proc_macro2::TokenStream = wit_bindgen_generate(bindgen_args.into()).into();
+    // Pull out wasmCloud-specific options, leaving only args wit-bindgen understands
+    let (provider_opts, bindgen_args) = match ProviderOpts::extract(bindgen_args) {
+        Ok(v) => v,
+        Err(e) => return e.to_compile_error(),
+    };
+    if let Err(e) = check_option_combinations(&provider_opts, &provider_generics) {
+        return e.to_compile_error();
+    }
 
-    // TODO: detect bindgen failure -- tokens just don't get generated
-    // ex. when exported world does not match package (as in package <ns>/<package>)
+    // With `ownership: Owning`, arguments that don't borrow are already in their owned form
+    let owning_bindings = is_owning_ownership(&bindgen_args);
+
+    // Perform wit-bindgen on the tokens that are bindgen args
+    let (wit_bindgen_ts, world_info) = wit_bindgen_generate(bindgen_args);
+    let world_has_imports = world_info.has_imports;
 
     // Parse the wit-bindgen generated tokens as a file
-    let mut wit_bindgen_ast: syn::File =
-        syn::parse2(wit_bindgen_ts).expect("failed to parse wit-bindgen generated code as file");
+    let mut wit_bindgen_ast: syn::File = match syn::parse2(wit_bindgen_ts) {
+        Ok(ast) => ast,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    // When wit-bindgen fails (ex. the WIT package fails to parse), it emits a `compile_error!`
+    // rather than the usual modules, which we forward as-is so the user sees the real error
+    if let Some(compile_error) = wit_bindgen_ast.items.iter().find(|i| is_compile_error(i)) {
+        // Older WIT declared worlds with `default world`, which is no longer valid syntax
+        if let Item::Macro(m) = compile_error {
+            if let Ok(msg) = syn::parse2::<LitStr>(m.mac.tokens.clone()) {
+                let msg = msg.value();
+                if msg.contains("default world") {
+                    return syn::Error::new(Span::call_site(), LEGACY_DEFAULT_WORLD_ERROR_TEXT)
+                        .to_compile_error();
+                }
+
+                // Only the first lines of the error (up to the excerpt of the WIT) are kept,
+                // without any backtrace that may follow
+                if msg.contains("depends on itself") {
+                    let wit_error = msg.split("\n\n").next().unwrap_or_default();
+                    return syn::Error::new(
+                        Span::call_site(),
+                        format!("{wit_error}\n\n{RECURSIVE_TYPE_ERROR_TEXT}"),
+                    )
+                    .to_compile_error();
+                }
+            }
+        }
+        return compile_error.to_token_stream();
+    }
 
-    // TODO: look for 'failed to parse'
-    // TREE:
-    // DEBUG: GENERATED AST? File { shebang: None, attrs: [], items: [Item::Macro { attrs: [], ident: None, mac: Macro { path: Path { leading_colon: Some(PathSep), segments: [PathSegment { ident: Ident { ident: "core", span: #5 bytes(0..66) }, arguments: PathArguments::None }, PathSep, PathSegment { ident: Ident { ident: "compile_error", span: #5 bytes(0..66) }, arguments: PathArguments::None }] }, bang_token: Not, delimiter: MacroDelimiter::Brace(Brace), tokens: TokenStream [Literal { kind: Str, symbol: "failed to parse package: /home/mrman/code/work/cosmonic/bindgen-test-kv/wit\\n\\nCaused by:\\n    expected `world`, `interface` or `use`, found an identifier\\n         --> /home/mrman/code/work/cosmonic/bindgen-test-kv/wit/keyvalue.wit:29:1\\n          |\\n       29 | default world keyvalue {
+    // Worlds that import nothing (ex. while scaffolding a provider) legitimately produce no bindings
+    if world_has_imports && is_empty_bindgen_output(&wit_bindgen_ast.items) {
+        return syn::Error::new(Span::call_site(), EMPTY_BINDGEN_OUTPUT_ERROR_TEXT)
+            .to_compile_error();
+    }
 
     // Visit the code that has been generated, to extract information we'll need to modify it
-    let mut visitor = WitBindgenOutputVisitor::default();
-    let _ = visitor.visit_file_mut(&mut wit_bindgen_ast);
+    let mut visitor = WitBindgenOutputVisitor {
+        flatten_fields: provider_opts.flatten_fields.clone(),
+        wit_ns_override: provider_opts.namespace.clone(),
+        wit_package_override: provider_opts.package.clone(),
+        package_versions: world_info.package_versions,
+        serde_path: provider_opts.serde_path.clone(),
+        deny_unknown_fields: provider_opts.deny_unknown_fields,
+        wit_field_names: provider_opts.wit_field_names,
+        ..Default::default()
+    };
+    visitor.visit_file_mut(&mut wit_bindgen_ast);
+
+    let mut lattice_fns = visitor.import_trait_fns.clone();
+
+    // Interfaces are gated by name, so names that match no interface would silently leave an interface ungated
+    if let Err(e) = check_interface_features(lattice_fns.keys(), &provider_opts) {
+        return e.to_compile_error();
+    }
+
+    // Only serve the interfaces selected by the provider (ex. when a shared world pulls in more than it handles)
+    if let Err(e) = select_served_interfaces(&mut lattice_fns, &provider_opts) {
+        return e.to_compile_error();
+    }
+
+    // Interfaces are named by their unique names in generated code, which are mapped back to the interfaces
+    // for looking up their options
+    let iface_paths_by_name = unique_interface_names(lattice_fns.keys())
+        .into_iter()
+        .map(|(p, name)| (name, p))
+        .collect::<HashMap<WitInterfaceName, WitInterfacePath>>();
 
     // Turn the function calls into object declarations for receiving from lattice
-    let methods_by_iface = if let Some(pkg) = &visitor.wit_package {
-        build_lattice_methods_by_wit_interface(
-            pkg,
+    let methods_by_iface = if !visitor.wit_packages.is_empty() {
+        match build_lattice_methods_by_wit_interface(
             &visitor.serde_extended_structs,
-            &visitor.import_trait_fns,
-        )
+            &visitor.owned_struct_forms,
+            &lattice_fns,
+            &visitor.import_module_paths,
+            &provider_opts,
+            owning_bindings,
+        ) {
+            Ok(methods) => methods,
+            Err(e) => return e.to_compile_error(),
+        }
+    } else if !world_has_imports {
+        // A provider for a world that imports nothing handles no methods, so every invocation is rejected
+        HashMap::new()
     } else {
-        panic!("failed to parse top-level WIT package name while reading bindgen output")
+        return syn::Error::new(Span::call_site(), UNDETECTED_PACKAGE_ERROR_TEXT)
+            .to_compile_error();
     };
 
-    // Convert AST that was generated by wit-bindgen to a TokenStream for use
-    let wit_bindgen_ast_tokens = wit_bindgen_ast.to_token_stream();
+    // All interface traits are implemented on the provider struct, so a method name shared by two
+    // interfaces would make the calls to the provider's own methods ambiguous
+    if let Err(e) = check_method_name_collisions(&methods_by_iface) {
+        return e.to_compile_error();
+    }
 
-    // Generate wit interface specific code for each interface
-    let mut iface_tokens = proc_macro2::TokenStream::new();
-    for (wit_iface_name, methods) in methods_by_iface.iter() {
-        let wit_iface = Ident::new(wit_iface_name, Span::call_site());
+    // Methods added to (or removed from) the WIT must be acknowledged by the provider's author, if requested
+    if let Some(acknowledged) = &provider_opts.acknowledged_methods {
+        if let Err(e) = check_acknowledged_methods(acknowledged, &methods_by_iface) {
+            return e.to_compile_error();
+        }
+    }
 
-        // Generate lists that will be iterated in tandem to build out functionality
-        let struct_names = methods
-            .clone()
-            .into_iter()
-            .map(|LatticeMethod { struct_name, .. }| struct_name)
-            .collect::<Vec<proc_macro2::Ident>>();
-        let struct_members = methods
-            .clone()
-            .into_iter()
-            .map(|LatticeMethod { struct_members, .. }| struct_members)
-            .collect::<Vec<proc_macro2::TokenStream>>();
-        let lattice_method_names = methods
-            .clone()
-            .into_iter()
-            .map(
-                |LatticeMethod {
-                     lattice_method_name,
-                     ..
-                 }| lattice_method_name,
-            )
-            .collect::<Vec<LitStr>>();
-        let func_names = methods
-            .clone()
-            .into_iter()
-            .map(|LatticeMethod { func_name, .. }| func_name)
-            .collect::<Vec<Ident>>();
-        let invocation_args = methods
-            .clone()
-            .into_iter()
-            .map(
-                |LatticeMethod {
-                     invocation_args, ..
-                 }| invocation_args,
-            )
-            .collect::<Vec<Vec<Ident>>>();
-        let invocation_returns = methods
-            .clone()
-            .into_iter()
-            .map(
-                |LatticeMethod {
-                     invocation_return, ..
-                 }| invocation_return,
-            )
-            .collect::<Vec<ReturnType>>();
+    // A provider sharing the name of a generated item would conflict with its definition
+    if let Err(e) = check_provider_name_collisions(
+        &impl_struct_name,
+        &wit_bindgen_ast.items,
+        &methods_by_iface,
+        &provider_opts,
+    ) {
+        return e.to_compile_error();
+    }
 
-        // TODO: bug here -- multiple interfaces means multiple impl blocks for Message Dispatch
-        // they must be combined
+    // Convert AST that was generated by wit-bindgen to a TokenStream for use, annotating its items while they
+    // are still parsed, so that the (large) wit-bindgen output is never parsed a second time
+    allow_generated_lints(&mut wit_bindgen_ast.items);
+    let wit_bindgen_ast_tokens = wit_bindgen_ast.into_token_stream();
 
-        iface_tokens.append_all(quote::quote!(
-            // START => Generated imports for method invocations via lattice
-            #(
-                #[derive(Debug, ::serde::Serialize, ::serde::Deserialize)]
-                struct #struct_names {
-                    #struct_members
-                }
-            )*
-            // END => Generated imports for method invocations via lattice
+    let codegen = ProviderCodegen::new(&impl_struct_name, &provider_generics, &provider_opts);
 
-            /// MessageDispatch ensures that your provider can receive and
-            /// process messages sent to it over the lattice
-            ///
-            /// This implementation is a stub and must be filled out by implementers
-            #[async_trait]
-            impl ::wasmcloud_provider_sdk::MessageDispatch for #impl_struct_name {
-                async fn dispatch<'a>(
-                    &'a self,
-                    ctx: ::wasmcloud_provider_sdk::Context,
-                    method: String,
-                    body: std::borrow::Cow<'a, [u8]>,
-                ) -> Result<Vec<u8>, ::wasmcloud_provider_sdk::error::ProviderInvocationError> {
-                    match method.as_str() {
-                        #(
-                            #lattice_method_names => {
-                                let input: #struct_names = ::wasmcloud_provider_sdk::deserialize(&body)?;
-                                let result = self
-                                    .#func_names(
-                                        ctx,
-                                        #(
-                                            input.#invocation_args,
-                                        )*
-                                    )
-                                    .await
-                                    .map_err(|e| {
-                                        ::wasmcloud_provider_sdk::error::ProviderInvocationError::Provider(e.to_string())
-                                    })?;
-                                Ok(::wasmcloud_provider_sdk::serialize(&result)?)
-                            }
-                        )*
-                        _ => Err(::wasmcloud_provider_sdk::error::InvocationError::Malformed(format!(
-                            "Invalid method name {method}",
-                        ))
-                                 .into()),
-                    }
-                }
-            }
+    // Generate wit interface specific code for each interface, which is gathered across interfaces for
+    // the items of the provider as a whole (ex. its single MessageDispatch impl)
+    let mut iface_code = InterfaceCode::default();
+    for (wit_iface_name, methods) in methods_by_iface.iter() {
+        // Interfaces gated behind a feature of the provider have all of their generated code gated as well
+        let iface_cfg = interface_cfg(&iface_paths_by_name[wit_iface_name], &provider_opts);
+        if let Err(e) = codegen.build_interface(
+            &mut iface_code,
+            wit_iface_name,
+            methods,
+            iface_cfg.as_ref(),
+            &visitor,
+        ) {
+            return e.to_compile_error();
+        }
+    }
 
-            #[async_trait]
-            pub trait #wit_iface {
-                #(
-                    async fn #func_names (
-                        &self,
-                        ctx: ::wasmcloud_provider_sdk::Context,
-                        #struct_members,
-                    ) #invocation_returns;
-                )*
-            }
+    // WIT flags are (de)serialized as their bits
+    let flags_serde_tokens = build_flags_serde_impls(
+        &visitor.serde_extended_structs,
+        &visitor.flags_types,
+        &codegen.serde_path,
+    );
 
-            #[async_trait]
-            impl #wit_iface for #impl_struct_name {
-                #(
-                    async fn #func_names (
-                        &self,
-                        ctx: ::wasmcloud_provider_sdk::Context,
-                        #struct_members,
-                    ) #invocation_returns {
-                        self.#func_names(
-                            ctx,
-                            #(
-                                #invocation_args,
-                            )*
-                        ).await
-                    }
-                )*
-            }
+    // Build clients for calling imported host functions, with errors converted to the provider's error type
+    let host_client_tokens = match &provider_opts.host_error {
+        Some(host_error) => build_host_clients(
+            &visitor.serde_extended_structs,
+            &visitor.import_trait_fns,
+            &visitor.import_module_paths,
+            host_error,
+        ),
+        None => proc_macro2::TokenStream::new(),
+    };
 
-        ));
-    }
+    let handler_tokens = codegen.build_handler();
+    let dispatch_tokens = codegen.build_dispatch(&iface_code);
+    let metadata_tokens = codegen.build_metadata(&methods_by_iface, &iface_paths_by_name);
+    let entrypoint_tokens = codegen.build_entrypoint();
+    let concurrency_module_tokens = codegen.build_concurrency_module(&iface_code);
+    let wire_format_tokens = codegen.build_wire_format_module();
+    let bench_module_tokens = codegen.build_bench_module(&iface_code);
+    let round_trip_tests_module_tokens = codegen.build_round_trip_tests_module(&iface_code);
+    let iface_tokens = iface_code.iface_tokens;
 
     // Build the token stream that wasmcloud will add on (not wit-bindgen specific)
-    let wasmcloud_ts = quote::quote!(
-        use ::serde::{Serialize, Deserialize};
-        use ::async_trait::async_trait;
-
+    //
+    // Generated items must not trip lints in providers that deny warnings, so each of them allows lints, rather
+    // than being wrapped in a module, as providers refer to the generated items by their paths
+    quote::quote!(
+        // NOTE: nothing is imported into the provider's module (which may already import the same names),
+        // so generated code refers to items of other crates by their full paths
+        //
         // START => Codegen performed by wit-bindgen
         #wit_bindgen_ast_tokens
         // END => Codegen performed by wit-bindgen
 
-        /// ProviderHandler ensures that your provider handles the basic
-        /// required functionality of all Providers on a wasmCloud lattice.
-        ///
-        /// This implementation is a stub and must be filled out by implementers
-        #[async_trait]
-        impl ::wasmcloud_provider_sdk::ProviderHandler for #impl_struct_name {
-            async fn put_link(&self, ld: &::wasmcloud_provider_sdk::core::LinkDefinition) -> bool {
-                self._put_link(ld).await
-            }
+        #flags_serde_tokens
 
-            async fn delete_link(&self, actor_id: &str) {
-                self._delete_link(actor_id).await
-            }
+        #handler_tokens
 
-            async fn shutdown(&self) {
-                self._shutdown().await
-            }
-        }
+        #dispatch_tokens
 
-        /// Given the implementation of ProviderHandler and MessageDispatch,
-        /// the implementation for your struct is a guaranteed
-        impl ::wasmcloud_provider_sdk::Provider for #impl_struct_name {}
+        #metadata_tokens
+
+        #entrypoint_tokens
 
         // START => per-interface traits & impl
         #iface_tokens
         // END => per-interface traits & impl
 
-        // TODO: OTEL integration w/ cfg_attr
-    );
-
-    // Chain all bits of generated code together
-    // let ts = wit_bindgen_ts.into_iter().chain(wasmcloud_ts.into_iter());
-    // proc_macro2::TokenStream::from_iter(ts).into()
-    wasmcloud_ts.into()
-}
-
-/// A struct for visiting the output of wit-bindgen
-/// focused around gathering all the important declarations we care about
-#[derive(Default)]
-struct WitBindgenOutputVisitor {
-    /// The detected namespace of the WIT file
-    wit_ns: Option<WitNamespaceName>,
+        #concurrency_module_tokens
 
-    /// The detected package of the WIT file
-    wit_package: Option<WitPackageName>,
+        #wire_format_tokens
 
-    /// Parents of the current module being traversed
-    parents: Vec<Ident>,
+        #host_client_tokens
 
-    /// Top level module that does contains all WIT exports
-    /// normally with internal modules starting from namespace
-    /// ex. ('exports' -> <WIT namespace> -> <WIT pkg>)
-    exports_ns_module: Option<ItemMod>,
+        #bench_module_tokens
 
-    /// Structs that were modified and extended to derive Serialize/Deserialize
-    serde_extended_structs: HashMap<String, Punctuated<syn::PathSegment, PathSep>>,
+        #round_trip_tests_module_tokens
 
-    /// Functions in traits that we'll have to stub eventually
-    import_trait_fns: HashMap<WitInterfaceName, Vec<ItemFn>>,
+    )
 }
 
-impl WitBindgenOutputVisitor {
-    fn is_wit_ns(&self, s: impl AsRef<str>) -> bool {
-        if let Some(v) = &self.wit_ns {
-            v == s.as_ref()
-        } else {
-            false
-        }
+/// Check that the options given to `generate!` can be used together, and with the provider they are given for
+fn check_option_combinations(
+    provider_opts: &ProviderOpts,
+    provider_generics: &syn::Generics,
+) -> syn::Result<()> {
+    // Benchmarks are generated in a nested module, where the bounds of a generic provider's params may not resolve
+    if provider_opts.bench_harness && !provider_generics.params.is_empty() {
+        return Err(syn::Error::new(
+            provider_generics.span(),
+            "bench_harness is not supported for generic providers",
+        ));
     }
 
-    fn current_module_level(&self) -> usize {
-        self.parents.len()
+    // The SDK's `MessageDispatch` requires `Send` futures, so a provider whose methods' futures aren't `Send`
+    // must implement dispatch itself (ex. running them on a `LocalSet`), as must the per-interface traits
+    if provider_opts.local_futures
+        && (!provider_opts.custom_dispatch || provider_opts.trait_style != TraitStyle::AsyncTrait)
+    {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "local_futures requires `dispatch: false` (as MessageDispatch futures must be Send) \
+             and the default `async_trait` trait style",
+        ));
     }
 
-    fn current_module_name(&self) -> Option<String> {
-        self.parents.last().map(|v| v.to_string())
+    // Links are passed to `_put_link` with a single form of config
+    if provider_opts.config.is_some() && provider_opts.config_map {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "`config` and `config_map` cannot both be set",
+        ));
     }
 
-    /// Check whether a the current node is directly under the wasm namespace
-    /// Primarily used for detecting the package
-    /// i.e. '<ns>/<package>'
-    fn at_wit_ns_module_child(&self) -> bool {
-        self.parents
-            .last()
-            .is_some_and(|ps| self.is_wit_ns(ps.to_string()))
+    // Blocking methods are called from plain (non-async) traits, which have no style or Send-ness to choose
+    if provider_opts.mode == MethodMode::Sync
+        && (provider_opts.local_futures || provider_opts.trait_style != TraitStyle::AsyncTrait)
+    {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "`mode: \"sync\"` cannot be combined with trait_style or local_futures",
+        ));
     }
 
-    /// Check whether the direct parent has a given name value
-    fn at_child_of_module(&self, name: impl AsRef<str>) -> bool {
-        self.parents.last().is_some_and(|v| v == name.as_ref())
+    // A codec replaces the wire format entirely
+    if let (Some(codec), WireFormat::Msgpack | WireFormat::Json) =
+        (&provider_opts.codec, provider_opts.wire_format)
+    {
+        return Err(syn::Error::new(
+            codec.span(),
+            "codec and wire_format cannot be used together, as the codec determines the wire format",
+        ));
     }
 
-    /// Check whether the direct parent has a given name value
-    fn at_grandchild_of_module(&self, name: impl AsRef<str>) -> bool {
-        match self.parents.len() {
-            len if len >= 2 => self.parents[len - 2] == name.as_ref(),
-            _ => false,
-        }
-    }
+    Ok(())
+}
 
-    /// Check whether we are currently at a module *below* the 'exports' known module name
-    fn at_exported_module(&self) -> bool {
-        self.parents.iter().any(|v| v == EXPORTS_MODULE_NAME)
-    }
+/// Code generated for the interfaces handled by a provider, gathered across interfaces so that
+/// the items of the provider as a whole (ex. its single MessageDispatch impl) cover every interface
+#[derive(Default)]
+struct InterfaceCode {
+    /// Invocation structs, traits and clients of every interface
+    iface_tokens: proc_macro2::TokenStream,
+    /// Arms of the match on the invoked operation, one per method
+    dispatch_arms: proc_macro2::TokenStream,
+    /// Variants of the `Operation` enum, one per method
+    operation_variants: Vec<Ident>,
+    /// Names of the lattice methods of the operations, in the same order
+    operation_names: Vec<LitStr>,
+    /// `cfg`s gating the operations of interfaces that are behind a feature, in the same order
+    operation_cfgs: Vec<proc_macro2::TokenStream>,
+    /// Semaphores limiting the concurrency of specific methods
+    concurrency_tokens: proc_macro2::TokenStream,
+    /// Round-trip tests of the invocation structs
+    round_trip_test_tokens: proc_macro2::TokenStream,
+    /// Benchmarks of dispatching each method
+    bench_tokens: proc_macro2::TokenStream,
 }
 
-/// Rust module name that is used by wit-bindgen to generate all the modules
-const EXPORTS_MODULE_NAME: &str = "exports";
+/// Settings of the code generated for a provider, which are derived from the provider and its options
+/// and shared by the builders of each part of that code (dispatch, handler, clients and metadata)
+struct ProviderCodegen<'a> {
+    /// Path to the impl struct (ex. `YourProvider` or `crate::providers::YourProvider`)
+    impl_struct_name: &'a Path,
+    /// Path to the impl struct from modules generated inside the provider's module
+    nested_impl_struct_name: proc_macro2::TokenStream,
+    /// Generic params of the provider (ex. `<B: Backend>`), which every impl for the provider is generic over
+    generics: &'a syn::Generics,
+    provider_opts: &'a ProviderOpts,
+    /// Paths to the crates that generated code depends on, which may be renamed or re-exported
+    /// (ex. by a facade crate in a monorepo)
+    sdk_path: Path,
+    serde_path: Path,
+    async_trait_path: Path,
+    /// Paths to the crates that generated code depends on, from modules generated inside the provider's module
+    nested_sdk_path: proc_macro2::TokenStream,
+    nested_async_trait_path: proc_macro2::TokenStream,
+    /// serde's derives refer to the `serde` crate unless pointed elsewhere
+    serde_crate_attr: Option<proc_macro2::TokenStream>,
+    /// Every generated item allows lints, as providers that deny warnings have no control over them
+    allow_lints: Attribute,
+    /// Functions that (de)serialize invocations and their results, from the perspective of the
+    /// provider's module and of modules generated inside it
+    serialize_fn: proc_macro2::TokenStream,
+    deserialize_fn: proc_macro2::TokenStream,
+    nested_serialize_fn: proc_macro2::TokenStream,
+    nested_deserialize_fn: proc_macro2::TokenStream,
+    /// Claims extracted from the context of each invocation are passed to the provider's methods after it
+    claims_param: proc_macro2::TokenStream,
+    claims_arg: proc_macro2::TokenStream,
+    claims_extraction: proc_macro2::TokenStream,
+    /// Blocking methods are called directly, rather than awaited
+    asyncness: proc_macro2::TokenStream,
+    await_tokens: proc_macro2::TokenStream,
+}
 
-impl VisitMut for WitBindgenOutputVisitor {
-    fn visit_item_mod_mut(&mut self, node: &mut ItemMod) {
-        debug_print(format!(
-            "{}> [(lvl {}) module:{:?}]",
-            "=".repeat(self.current_module_level()),
-            self.current_module_level(),
-            node.ident
-        ));
+impl<'a> ProviderCodegen<'a> {
+    fn new(
+        impl_struct_name: &'a Path,
+        generics: &'a syn::Generics,
+        provider_opts: &'a ProviderOpts,
+    ) -> Self {
+        let sdk_path: Path = provider_opts
+            .sdk_path
+            .clone()
+            .unwrap_or_else(|| syn::parse_quote!(::wasmcloud_provider_sdk));
+        let serde_path: Path = provider_opts
+            .serde_path
+            .clone()
+            .unwrap_or_else(|| syn::parse_quote!(::serde));
+        let async_trait_path: Path = provider_opts
+            .async_trait_path
+            .clone()
+            .unwrap_or_else(|| syn::parse_quote!(::async_trait));
+        let serde_crate_attr = provider_opts.serde_path.as_ref().map(|p| {
+            let p = pretty_tokens(p.to_token_stream());
+            quote::quote!(#[serde(crate = #p)])
+        });
 
-        // Save the WIT namespace that we've recognized
-        //
-        // ASSUMPTION: The top level WIT namespace is always a module at @ level zero
-        // of the generated output
-        if self.current_module_level() == 0 && node.ident != EXPORTS_MODULE_NAME {
-            self.wit_ns = Some(node.ident.to_string());
-        }
+        let (serialize_fn, deserialize_fn, nested_serialize_fn, nested_deserialize_fn) =
+            match (&provider_opts.codec, provider_opts.wire_format) {
+                (Some(codec), _) => {
+                    let nested_codec = nested_path(codec);
+                    (
+                        quote::quote!(<#codec as self::InvocationCodec>::encode),
+                        quote::quote!(<#codec as self::InvocationCodec>::decode),
+                        quote::quote!(<#nested_codec as super::InvocationCodec>::encode),
+                        quote::quote!(<#nested_codec as super::InvocationCodec>::decode),
+                    )
+                }
+                (None, WireFormat::Sdk) => (
+                    quote::quote!(#sdk_path::serialize),
+                    quote::quote!(#sdk_path::deserialize),
+                    quote::quote!(#sdk_path::serialize),
+                    quote::quote!(#sdk_path::deserialize),
+                ),
+                (None, WireFormat::Msgpack | WireFormat::Json) => (
+                    quote::quote!(self::wire_format::serialize),
+                    quote::quote!(self::wire_format::deserialize),
+                    quote::quote!(super::wire_format::serialize),
+                    quote::quote!(super::wire_format::deserialize),
+                ),
+            };
 
-        // Save the WIT package name
-        //
-        // ASSUMPTION: The level 1 modules in the detected top level wasm namespace
-        // is the package the top level WIT package
-        if self.current_module_level() == 1
-        // If we're one level in and the closest parent is the wasm namespace,
-        // we know this must be the package name
-            && self.at_wit_ns_module_child()
-            && !self.at_exported_module()
-        {
-            self.wit_package = Some(node.ident.to_string());
-        }
+        let (claims_param, claims_arg, claims_extraction) = match &provider_opts.claims {
+            Some(claims) => (
+                quote::quote!(claims: #claims,),
+                quote::quote!(claims,),
+                quote::quote!(
+                    let claims = <#claims as ::core::convert::TryFrom<&#sdk_path::Context>>::try_from(&ctx)
+                        .map_err(|e| {
+                            #sdk_path::error::ProviderInvocationError::Provider(format!(
+                                "failed to extract claims from the invocation context: {e}"
+                            ))
+                        })?;
+                ),
+            ),
+            None => Default::default(),
+        };
 
-        // Recognize the 'exports' module which contains
-        // all the exported interfaces
-        //
-        // ASSUMPTION: all exported modules are put into a level 0 'exports' module
-        // which contains the top level namespace again
-        if self.current_module_level() == 1 && self.at_child_of_module(EXPORTS_MODULE_NAME) {
-            // this would be the ('exports' -> <ns>) node, note 'exports' itself.
-            self.exports_ns_module = Some(node.clone());
+        let (asyncness, await_tokens) = match provider_opts.mode {
+            MethodMode::Async => (quote::quote!(async), quote::quote!(.await)),
+            MethodMode::Sync => Default::default(),
+        };
+
+        Self {
+            impl_struct_name,
+            nested_impl_struct_name: nested_path(impl_struct_name),
+            generics,
+            provider_opts,
+            nested_sdk_path: nested_path(&sdk_path),
+            nested_async_trait_path: nested_path(&async_trait_path),
+            sdk_path,
+            serde_path,
+            async_trait_path,
+            serde_crate_attr,
+            allow_lints: allow_lints_attr(),
+            serialize_fn,
+            deserialize_fn,
+            nested_serialize_fn,
+            nested_deserialize_fn,
+            claims_param,
+            claims_arg,
+            claims_extraction,
+            asyncness,
+            await_tokens,
         }
+    }
 
-        // ASSUMPTION: level 2 modules contain externally visible *or* used interfaces
-        // (i.e. ones that are exported)
-        // 'use' calls will  cause an interface to show up, but only if the
-        // thing that uses it is imported/exported
+    /// Build the code of an interface, adding it (along with what the items of the provider as a whole
+    /// need from the interface) to the code generated for the provider's interfaces
+    fn build_interface(
+        &self,
+        iface_code: &mut InterfaceCode,
+        wit_iface_name: &str,
+        methods: &[LatticeMethod],
+        iface_cfg: Option<&Attribute>,
+        visitor: &WitBindgenOutputVisitor,
+    ) -> syn::Result<()> {
+        let wit_iface = Ident::new(wit_iface_name, Span::call_site());
+        let iface_cfg_tokens = iface_cfg.map(ToTokens::to_token_stream).unwrap_or_default();
 
-        // Recur/Traverse deeper into the detected modules where possible
-        if let Some((_, ref mut items)) = &mut node.content {
-            // Save the current module before we go spelunking
-            self.parents.push(node.ident.clone());
+        let struct_decls = self.build_invocation_structs(methods);
+        let iface_trait_tokens = self.build_interface_trait(&wit_iface, methods);
+        let mut generated_iface_tokens = cfg_gate_items(
+            quote::quote!(
+                // START => Generated imports for method invocations via lattice
+                #(
+                    #struct_decls
+                )*
+                // END => Generated imports for method invocations via lattice
 
-            for mut item in items {
-                self.visit_item_mut(&mut item);
-            }
+                #iface_trait_tokens
+            ),
+            iface_cfg,
+        );
 
-            self.parents.pop();
+        self.build_dispatch_arms(iface_code, wit_iface_name, methods, iface_cfg);
+
+        if self.provider_opts.generate_tests {
+            iface_code
+                .round_trip_test_tokens
+                .append_all(self.build_round_trip_tests(methods, &iface_cfg_tokens));
+        }
+        if self.provider_opts.bench_harness {
+            iface_code
+                .bench_tokens
+                .append_all(self.build_benches(methods, &iface_cfg_tokens));
+        }
+
+        if self.provider_opts.lattice_clients {
+            generated_iface_tokens.append_all(cfg_gate_items(
+                self.build_lattice_client(&wit_iface, methods, visitor),
+                iface_cfg,
+            ));
+        }
+
+        // Code for the interface is inlined, or included from a file in `OUT_DIR` so it can be inspected
+        // while debugging
+        if self.provider_opts.split_output && debug_enabled() {
+            iface_code.iface_tokens.append_all(include_from_out_dir(
+                self.impl_struct_name,
+                wit_iface_name,
+                generated_iface_tokens,
+            )?);
         } else {
-            debug_print(format!("empty module: [{}]", node.ident));
+            iface_code.iface_tokens.append_all(generated_iface_tokens);
         }
+        Ok(())
     }
 
-    fn visit_item_mut(&mut self, node: &mut syn::Item) {
-        match node {
-            Item::Fn(f) => {
-                debug_print(format!(
-                    "{}> [(lvl {}) module:{:?}] visiting fn {}",
-                    "=".repeat(self.current_module_level()),
-                    self.current_module_level(),
-                    self.parents.last(),
-                    f.sig.ident
-                ));
-
-                // If we're visiting a function that is inside a non-export, and the grand parent
-                // is the top level package, we must gather the function calls to make lattice messages out of
+    /// Build the structs that invocations of the interface's methods are decoded into
+    ///
+    /// Invocation structs derive Default only when all of their members implement it, while methods
+    /// with a single unwrapped argument receive that argument directly, so their "struct" is an alias for it.
+    /// Structs can be converted into their arguments, for calling the functions generated by wit-bindgen
+    ///
+    /// Optional members are defaulted when missing, so peers built against older versions of the WIT
+    /// (which lack arguments added since) can still invoke the provider, even with formats that encode
+    /// structs as sequences. Members that are not in the WIT are ignored unless `deny_unknown_fields` is set
+    fn build_invocation_structs(&self, methods: &[LatticeMethod]) -> Vec<proc_macro2::TokenStream> {
+        let Self {
+            provider_opts,
+            serde_path,
+            serde_crate_attr,
+            allow_lints,
+            ..
+        } = self;
+        let additional_derives = &provider_opts.additional_derives;
+        let unknown_fields_attr = provider_opts
+            .deny_unknown_fields
+            .then(|| quote::quote!(#[serde(deny_unknown_fields)]));
+        methods
+            .iter()
+            .map(|m| {
+                let struct_name = &m.struct_name;
+                let args = &m.invocation_args;
+                let arg_types = &m.invocation_arg_types;
+                let arg_defaults = arg_types.iter().map(|ty| {
+                    syn::parse2::<syn::Type>(ty.clone())
+                        .is_ok_and(|ty| is_option_type(&ty))
+                        .then(|| quote::quote!(#[serde(default)]))
+                });
+                let arg_renames = args.iter().map(|arg| {
+                    provider_opts
+                        .wit_field_names
+                        .then(|| wit_field_name_attr(arg))
+                        .flatten()
+                });
+                let struct_members =
+                    quote::quote!(#(#arg_defaults #arg_renames #args: #arg_types,)*);
+                let into_args = quote::quote!(
+                    #allow_lints
+                    impl #struct_name {
+                        /// Convert the invocation into its arguments, in the order of the WIT function's parameters
+                        fn into_args(self) -> (#(#arg_types,)*) {
+                            (#(self.#args,)*)
+                        }
+                    }
+                );
+                match &m.single_arg_type {
+                    Some(ty) => quote::quote!(
+                        #allow_lints
+                        type #struct_name = #ty;
+                    ),
+                    None if m.members_default => quote::quote!(
+                        #allow_lints
+                        #[derive(Debug, Clone, Default, #serde_path::Serialize, #serde_path::Deserialize #(, #additional_derives)*)]
+                        #serde_crate_attr
+                        #unknown_fields_attr
+                        struct #struct_name {
+                            #struct_members
+                        }
+
+                        #into_args
+                    ),
+                    None => quote::quote!(
+                        #allow_lints
+                        #[derive(Debug, Clone, #serde_path::Serialize, #serde_path::Deserialize #(, #additional_derives)*)]
+                        #serde_crate_attr
+                        #unknown_fields_attr
+                        struct #struct_name {
+                            #struct_members
+                        }
+
+                        #into_args
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    /// Build the per-interface trait, and the impl of that trait which delegates to
+    /// the inherent methods implemented by the provider
+    fn build_interface_trait(
+        &self,
+        wit_iface: &Ident,
+        methods: &[LatticeMethod],
+    ) -> proc_macro2::TokenStream {
+        let Self {
+            impl_struct_name,
+            provider_opts,
+            sdk_path,
+            async_trait_path,
+            allow_lints,
+            claims_param,
+            claims_arg,
+            asyncness,
+            await_tokens,
+            ..
+        } = self;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+
+        // The trait may be suffixed, as it would otherwise share the name of the trait that wit-bindgen
+        // generates for the interface (ex. `KeyValueProvider` rather than `KeyValue`)
+        let wit_iface_trait = format_ident!("{}{}", wit_iface, provider_opts.trait_suffix);
+
+        let func_names = methods
+            .iter()
+            .map(|m| &m.func_name)
+            .collect::<Vec<&Ident>>();
+        let invocation_args = methods
+            .iter()
+            .map(|m| &m.invocation_args)
+            .collect::<Vec<&Vec<Ident>>>();
+        let invocation_returns = methods
+            .iter()
+            .map(|m| &m.invocation_return)
+            .collect::<Vec<&ReturnType>>();
+        let docs = methods
+            .iter()
+            .map(|m| &m.docs)
+            .collect::<Vec<&Vec<Attribute>>>();
+        let fn_params = build_trait_fn_params(methods);
+
+        let trait_fns = quote::quote!(
+            #(
+                #(#docs)*
+                #asyncness fn #func_names (
+                    &self,
+                    ctx: #sdk_path::Context,
+                    #claims_param
+                    #fn_params
+                ) #invocation_returns;
+            )*
+        );
+        let impl_fns = quote::quote!(
+            #(
+                #(#docs)*
+                #asyncness fn #func_names (
+                    &self,
+                    ctx: #sdk_path::Context,
+                    #claims_param
+                    #fn_params
+                ) #invocation_returns {
+                    self.#func_names(
+                        ctx,
+                        #claims_arg
+                        #(
+                            #invocation_args,
+                        )*
+                    )#await_tokens
+                }
+            )*
+        );
+        match provider_opts.trait_style {
+            // Blocking methods need no async trait machinery
+            _ if provider_opts.mode == MethodMode::Sync => quote::quote!(
+                #allow_lints
+                pub trait #wit_iface_trait {
+                    #trait_fns
+                }
+
+                #allow_lints
+                impl #impl_generics #wit_iface_trait for #impl_struct_name #ty_generics #where_clause {
+                    #impl_fns
+                }
+            ),
+            TraitStyle::AsyncTrait => {
+                let async_trait = if provider_opts.local_futures {
+                    quote::quote!(#[#async_trait_path::async_trait(?Send)])
+                } else {
+                    quote::quote!(#[#async_trait_path::async_trait])
+                };
+                quote::quote!(
+                    #allow_lints
+                    #async_trait
+                    pub trait #wit_iface_trait {
+                        #trait_fns
+                    }
+
+                    #allow_lints
+                    #async_trait
+                    impl #impl_generics #wit_iface_trait for #impl_struct_name #ty_generics #where_clause {
+                        #impl_fns
+                    }
+                )
+            }
+            // The local (non-Send) trait is declared, and trait_variant produces
+            // the Send-bounded trait that is named after the interface
+            TraitStyle::TraitVariant => {
+                let local_wit_iface = format_ident!("Local{}", wit_iface_trait);
+                quote::quote!(
+                    #allow_lints
+                    #[::trait_variant::make(#wit_iface_trait: Send)]
+                    pub trait #local_wit_iface {
+                        #trait_fns
+                    }
+
+                    #allow_lints
+                    impl #impl_generics #wit_iface_trait for #impl_struct_name #ty_generics #where_clause {
+                        #impl_fns
+                    }
+                )
+            }
+            // Methods return explicitly boxed futures rather than being declared `async`
+            TraitStyle::BoxedFuture => {
+                let outputs = invocation_returns
+                    .iter()
+                    .map(|r| match r {
+                        ReturnType::Default => quote::quote!(()),
+                        ReturnType::Type(_, ty) => ty.to_token_stream(),
+                    })
+                    .collect::<Vec<proc_macro2::TokenStream>>();
+                quote::quote!(
+                    #allow_lints
+                    pub trait #wit_iface_trait {
+                        #(
+                            #(#docs)*
+                            fn #func_names (
+                                &self,
+                                ctx: #sdk_path::Context,
+                                #claims_param
+                                #fn_params
+                            ) -> ::std::pin::Pin<Box<dyn ::std::future::Future<Output = #outputs> + Send + '_>>;
+                        )*
+                    }
+
+                    #allow_lints
+                    impl #impl_generics #wit_iface_trait for #impl_struct_name #ty_generics #where_clause {
+                        #(
+                            #(#docs)*
+                            fn #func_names (
+                                &self,
+                                ctx: #sdk_path::Context,
+                                #claims_param
+                                #fn_params
+                            ) -> ::std::pin::Pin<Box<dyn ::std::future::Future<Output = #outputs> + Send + '_>> {
+                                Box::pin(self.#func_names(
+                                    ctx,
+                                    #claims_arg
+                                    #(
+                                        #invocation_args,
+                                    )*
+                                ))
+                            }
+                        )*
+                    }
+                )
+            }
+        }
+    }
+
+    /// Build the dispatch arms of the interface's methods, along with their operations
+    ///
+    /// Dispatch arms for this interface are combined with those of all other interfaces,
+    /// since the provider can only have a single MessageDispatch impl
+    fn build_dispatch_arms(
+        &self,
+        iface_code: &mut InterfaceCode,
+        wit_iface_name: &str,
+        methods: &[LatticeMethod],
+        iface_cfg: Option<&Attribute>,
+    ) {
+        let Self {
+            provider_opts,
+            sdk_path,
+            serialize_fn,
+            deserialize_fn,
+            claims_arg,
+            claims_extraction,
+            await_tokens,
+            ..
+        } = self;
+        let iface_cfg_tokens = iface_cfg.map(ToTokens::to_token_stream).unwrap_or_default();
+
+        // Authorization checks performed before invoking methods that require them,
+        // which consult the provider's `_authorize` hook
+        let authorization_checks = methods
+            .iter()
+            .map(|m| {
+                if !provider_opts
+                    .authorize_methods
+                    .contains(&m.func_name.unraw().to_string())
+                {
+                    return proc_macro2::TokenStream::new();
+                }
+                let lattice_method_name = &m.lattice_method_name;
+                quote::quote!(
+                    if !self._authorize(&ctx, #lattice_method_name).await {
+                        return Err(#sdk_path::error::ProviderInvocationError::Provider(format!(
+                            "actor [{}] is not authorized to invoke [{}]",
+                            ctx.actor.as_deref().unwrap_or("<unknown>"),
+                            #lattice_method_name,
+                        )));
+                    }
+                )
+            })
+            .collect::<Vec<proc_macro2::TokenStream>>();
+
+        // Permits that must be acquired before invoking methods with limited concurrency,
+        // backed by a semaphore per method
+        let concurrency_permits = methods
+            .iter()
+            .map(|m| {
+                let Some(limit) = provider_opts
+                    .method_concurrency
+                    .get(&m.func_name.unraw().to_string())
+                else {
+                    return proc_macro2::TokenStream::new();
+                };
+                let semaphore =
+                    format_ident!("{}", m.struct_name.to_string().to_shouty_snake_case());
+                iface_code.concurrency_tokens.append_all(cfg_gate_items(
+                    quote::quote!(
+                        pub(super) static #semaphore: ::std::sync::OnceLock<::tokio::sync::Semaphore> =
+                            ::std::sync::OnceLock::new();
+                    ),
+                    iface_cfg,
+                ));
+                quote::quote!(
+                    let _permit = method_concurrency::#semaphore
+                        .get_or_init(|| ::tokio::sync::Semaphore::new(#limit))
+                        .acquire()
+                        .await
+                        .map_err(|e| {
+                            #sdk_path::error::ProviderInvocationError::Provider(e.to_string())
+                        })?;
+                )
+            })
+            .collect::<Vec<proc_macro2::TokenStream>>();
+
+        // Decoding of the invocation struct from the message body, which is skipped for
+        // functions without arguments since there is no payload to decode
+        //
+        // NOTE: the derived Deserialize impls accept structs encoded both positionally (msgpack arrays)
+        // and by name (msgpack maps), so payloads from actor SDKs using either encoding decode
+        // identically. Records with `flatten_fields` are the exception, as serde requires maps for them.
+        let decode_inputs = methods
+            .iter()
+            .map(|m| {
+                if m.invocation_args.is_empty() || m.raw {
+                    proc_macro2::TokenStream::new()
+                } else {
+                    let struct_name = &m.struct_name;
+                    quote::quote!(
+                        let input: #struct_name = #deserialize_fn(&body)?;
+                    )
+                }
+            })
+            .collect::<Vec<proc_macro2::TokenStream>>();
+
+        // Arguments passed to the provider's methods from the decoded input
+        let call_args = methods
+            .iter()
+            .map(|m| {
+                let args = &m.invocation_args;
+                if m.raw {
+                    quote::quote!(body.into_owned(),)
+                } else if m.single_arg_type.is_some() {
+                    quote::quote!(input,)
+                } else {
+                    quote::quote!(#(input.#args,)*)
+                }
+            })
+            .collect::<Vec<proc_macro2::TokenStream>>();
+
+        // Errors are stringified, unless they are structured (i.e. of a type defined by the WIT), in which case
+        // the whole result is serialized so the caller can reconstruct the typed error
+        //
+        // Only the outer `Result` of a method is converted, so that the ok value of a method returning
+        // `Result<Option<T>, E>` is sent as-is (as is the value of a method returning `Option<T>`), while
+        // infallible methods (ex. `-> u64`) have their value sent directly
+        let result_conversions = methods
+            .iter()
+            .map(|m| {
+                // A `result` without an error type (ex. WIT `result` or `result<u32>`) has a `()` error,
+                // which cannot be stringified, so the failure of the method is reported instead
+                let unit_error = result_err_type(&m.invocation_return)
+                    .is_some_and(|e| matches!(e, syn::Type::Tuple(t) if t.elems.is_empty()));
+                if m.structured_error || !m.fallible {
+                    proc_macro2::TokenStream::new()
+                } else if unit_error {
+                    let failed = format!("{} failed", m.lattice_method_name.value());
+                    quote::quote!(
+                        .map_err(|()| {
+                            #sdk_path::error::ProviderInvocationError::Provider(#failed.to_string())
+                        })?
+                    )
+                } else {
+                    quote::quote!(
+                        .map_err(|e| {
+                            #sdk_path::error::ProviderInvocationError::Provider(e.to_string())
+                        })?
+                    )
+                }
+            })
+            .collect::<Vec<proc_macro2::TokenStream>>();
+
+        // Results are serialized, except for raw methods which respond with their bytes as-is
+        let responses = methods
+            .iter()
+            .map(|m| {
+                if m.raw {
+                    quote::quote!(Ok(result))
+                } else {
+                    quote::quote!(Ok(#serialize_fn(&result)?))
+                }
+            })
+            .collect::<Vec<proc_macro2::TokenStream>>();
+
+        let wit_iface_name_lit = LitStr::new(wit_iface_name, Span::call_site());
+        let func_names = methods.iter().map(|m| &m.func_name);
+        let variants = methods
+            .iter()
+            .map(|m| format_ident!("{}", m.func_name.unraw().to_string().to_upper_camel_case()))
+            .collect::<Vec<Ident>>();
+        iface_code
+            .operation_variants
+            .extend(variants.iter().cloned());
+        iface_code
+            .operation_names
+            .extend(methods.iter().map(|m| m.lattice_method_name.clone()));
+        iface_code
+            .operation_cfgs
+            .extend(variants.iter().map(|_| iface_cfg_tokens.clone()));
+        iface_code.dispatch_arms.append_all(quote::quote!(
+            #(
+                #iface_cfg_tokens
+                Operation::#variants => {
+                    #[cfg(feature = "otel")]
+                    ::tracing::Span::current().record("interface", #wit_iface_name_lit);
+                    #authorization_checks
+                    #claims_extraction
+                    #concurrency_permits
+                    #decode_inputs
+                    let result = self
+                        .#func_names(
+                            ctx,
+                            #claims_arg
+                            #call_args
+                        )
+                        #await_tokens
+                        #result_conversions;
+                    #responses
+                }
+            )*
+        ));
+    }
+
+    /// Build round-trip tests of the wire format of each invocation struct that can be default-constructed
+    /// (methods without arguments receive no payload, so there is nothing to test)
+    fn build_round_trip_tests(
+        &self,
+        methods: &[LatticeMethod],
+        iface_cfg_tokens: &proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let Self {
+            nested_serialize_fn,
+            nested_deserialize_fn,
+            ..
+        } = self;
+        let mut tokens = proc_macro2::TokenStream::new();
+        for m in methods
+            .iter()
+            .filter(|m| m.members_default && !m.invocation_args.is_empty() && !m.raw)
+        {
+            let test_fn_name = format_ident!("round_trip_{}", m.func_name.unraw());
+            let struct_name = &m.struct_name;
+            tokens.append_all(quote::quote!(
+                #iface_cfg_tokens
+                #[test]
+                fn #test_fn_name() {
+                    let input = <super::#struct_name as ::core::default::Default>::default();
+                    let body = #nested_serialize_fn(&input)
+                        .expect("failed to serialize invocation");
+                    let decoded: super::#struct_name = #nested_deserialize_fn(&body)
+                        .expect("failed to deserialize invocation");
+                    let reencoded = #nested_serialize_fn(&decoded)
+                        .expect("failed to serialize decoded invocation");
+                    assert_eq!(body, reencoded, "invocation changed after a round trip");
+                }
+            ));
+        }
+        tokens
+    }
+
+    /// Build benchmarks for dispatching each method, given a representative invocation
+    fn build_benches(
+        &self,
+        methods: &[LatticeMethod],
+        iface_cfg_tokens: &proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let Self {
+            nested_impl_struct_name,
+            sdk_path,
+            nested_serialize_fn,
+            ..
+        } = self;
+        let bench_fn_names = methods
+            .iter()
+            .map(|m| format_ident!("bench_{}", m.func_name))
+            .collect::<Vec<Ident>>();
+        let struct_names = methods.iter().map(|m| &m.struct_name);
+        let lattice_method_names = methods
+            .iter()
+            .map(|m| &m.lattice_method_name)
+            .collect::<Vec<&LitStr>>();
+        let bench_bodies = methods
+            .iter()
+            .map(|m| {
+                let args = &m.invocation_args;
+                match (m.raw, &m.single_arg_type) {
+                    (true, Some(_)) => quote::quote!(input.clone()),
+                    (true, None) => quote::quote!(#(input.#args.clone())*),
+                    (false, _) => quote::quote!(
+                        #nested_serialize_fn(input).expect("failed to serialize invocation")
+                    ),
+                }
+            })
+            .collect::<Vec<proc_macro2::TokenStream>>();
+        quote::quote!(
+            #(
+                /// Measure the throughput of dispatching the given invocation
+                #iface_cfg_tokens
+                pub(super) fn #bench_fn_names(
+                    c: &mut ::criterion::Criterion,
+                    runtime: &::tokio::runtime::Runtime,
+                    provider: &#nested_impl_struct_name,
+                    input: &super::#struct_names,
+                ) {
+                    let body = #bench_bodies;
+                    c.bench_function(#lattice_method_names, |b| {
+                        b.to_async(runtime).iter(|| {
+                            #sdk_path::MessageDispatch::dispatch(
+                                provider,
+                                #sdk_path::Context::default(),
+                                #lattice_method_names.to_string(),
+                                ::std::borrow::Cow::Borrowed(&body),
+                            )
+                        })
+                    });
+                }
+            )*
+        )
+    }
+
+    /// Build a client that sends invocations of the interface to other entities on the lattice
+    fn build_lattice_client(
+        &self,
+        wit_iface: &Ident,
+        methods: &[LatticeMethod],
+        visitor: &WitBindgenOutputVisitor,
+    ) -> proc_macro2::TokenStream {
+        let Self {
+            sdk_path,
+            allow_lints,
+            serialize_fn,
+            deserialize_fn,
+            ..
+        } = self;
+        let client_name = format_ident!("{}LatticeClient", wit_iface);
+        let type_lookup = TypeLookup {
+            paths: &visitor.serde_extended_structs,
+            scope: None,
+            owned_forms: &visitor.owned_struct_forms,
+        };
+        let func_names = methods.iter().map(|m| &m.func_name);
+        let lattice_method_names = methods.iter().map(|m| &m.lattice_method_name);
+        let fn_params = build_trait_fn_params(methods);
+        let ok_types = methods
+            .iter()
+            .map(|m| match &m.invocation_return {
+                // Structured errors are received as part of the whole result
+                ReturnType::Type(_, ty) if m.structured_error => {
+                    syn::parse2(qualify_type(ty.to_token_stream(), type_lookup))
+                        .unwrap_or_else(|_| (**ty).clone())
+                }
+                ret => result_ok_type(ret, type_lookup),
+            })
+            .collect::<Vec<syn::Type>>();
+
+        // Values that are sent as input when invoking methods over the lattice
+        let (client_bodies, client_results): (Vec<_>, Vec<_>) = methods
+            .iter()
+            .map(|m| {
+                let struct_name = &m.struct_name;
+                let args = &m.invocation_args;
+                if m.raw {
+                    (quote::quote!(#(#args)*), quote::quote!(Ok(response)))
+                } else {
+                    let request_value = if m.single_arg_type.is_some() {
+                        quote::quote!(#(#args)*)
+                    } else {
+                        quote::quote!(#struct_name { #(#args,)* })
+                    };
+                    (
+                        quote::quote!(#serialize_fn(&#request_value)?),
+                        quote::quote!(Ok(#deserialize_fn(&response)?)),
+                    )
+                }
+            })
+            .unzip();
+        quote::quote!(
+            /// Client for invoking the methods of the interface on another entity over the lattice
+            #allow_lints
+            pub struct #client_name {
+                origin: #sdk_path::core::WasmCloudEntity,
+                target: #sdk_path::core::WasmCloudEntity,
+            }
+
+            #allow_lints
+            impl #client_name {
+                /// Create a client that sends invocations from `origin` (usually the provider itself) to `target`
+                pub fn new(
+                    origin: #sdk_path::core::WasmCloudEntity,
+                    target: #sdk_path::core::WasmCloudEntity,
+                ) -> Self {
+                    Self { origin, target }
+                }
+
+                #(
+                    pub async fn #func_names(
+                        &self,
+                        #fn_params
+                    ) -> Result<#ok_types, #sdk_path::error::ProviderInvocationError> {
+                        let body = #client_bodies;
+                        let response = #sdk_path::get_connection()
+                            .get_rpc_client()
+                            .send(
+                                self.origin.clone(),
+                                self.target.clone(),
+                                #sdk_path::core::Message {
+                                    method: #lattice_method_names,
+                                    arg: body.into(),
+                                },
+                            )
+                            .await
+                            .map_err(|e| {
+                                #sdk_path::error::ProviderInvocationError::Provider(e.to_string())
+                            })?;
+                        #client_results
+                    }
+                )*
+            }
+        )
+    }
+
+    /// Build the ProviderHandler impl, which passes links and lifecycle events to the provider's methods,
+    /// along with the fallbacks for the provider's optional methods and the Provider impl
+    fn build_handler(&self) -> proc_macro2::TokenStream {
+        let Self {
+            impl_struct_name,
+            provider_opts,
+            sdk_path,
+            serde_path,
+            async_trait_path,
+            nested_sdk_path,
+            nested_async_trait_path,
+            allow_lints,
+            await_tokens,
+            ..
+        } = self;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+
+        // Fallbacks for the optional methods of the provider, of which dispatch hooks are only called by generated dispatch
+        let mut fallback_traits = vec![FallbackTrait {
+            name: format_ident!("DefaultHealthRequest"),
+            doc: " Health check used when the provider does not define its own `_health_request()`",
+            methods: quote::quote!(
+                async fn _health_request(
+                    &self,
+                    _arg: &#nested_sdk_path::core::HealthCheckRequest,
+                ) -> #nested_sdk_path::core::HealthCheckResponse {
+                    #nested_sdk_path::core::HealthCheckResponse {
+                        healthy: true,
+                        message: None,
+                    }
+                }
+            ),
+        }];
+        if !provider_opts.custom_dispatch {
+            fallback_traits.push(FallbackTrait {
+                name: format_ident!("DefaultDispatchHooks"),
+                doc: " Dispatch hooks used when the provider does not define its own `_before_dispatch()` and `_after_dispatch()`, which do nothing",
+                methods: quote::quote!(
+                    async fn _before_dispatch(
+                        &self,
+                        _ctx: &#nested_sdk_path::Context,
+                        _method: &str,
+                    ) -> Result<(), #nested_sdk_path::error::ProviderInvocationError> {
+                        Ok(())
+                    }
+
+                    async fn _after_dispatch(
+                        &self,
+                        _ctx: &#nested_sdk_path::Context,
+                        _method: &str,
+                        _result: &Result<Vec<u8>, #nested_sdk_path::error::ProviderInvocationError>,
+                    ) {
+                    }
+                ),
+            });
+        }
+        let fallback_traits_tokens = build_fallback_traits(
+            impl_struct_name,
+            self.generics,
+            &fallback_traits,
+            async_trait_path,
+            nested_async_trait_path,
+        );
+
+        // Pass links to the provider, along with their config values decoded into the config type if one was given
+        let put_link_tokens = match &provider_opts.config {
+            Some(config) => quote::quote!(
+                let values = ld.values.iter().map(|(k, v)| (k.as_str(), v.as_str()));
+                let config = match <#config as #serde_path::Deserialize>::deserialize(
+                    #serde_path::de::value::MapDeserializer::<_, #serde_path::de::value::Error>::new(values),
+                ) {
+                    Ok(config) => config,
+                    // The link is rejected when its config is invalid
+                    Err(error) => {
+                        ::tracing::error!(
+                            link_name = %ld.link_name,
+                            actor_id = %ld.actor_id,
+                            %error,
+                            "failed to decode link config",
+                        );
+                        return false;
+                    }
+                };
+                self._put_link(config, ld)#await_tokens
+            ),
+            None if provider_opts.config_map => quote::quote!(
+                let config: ::std::collections::HashMap<String, String> = ld
+                    .values
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                self._put_link(config, ld)#await_tokens
+            ),
+            None => quote::quote!(self._put_link(ld)#await_tokens),
+        };
+
+        quote::quote!(
+            /// ProviderHandler ensures that your provider handles the basic
+            /// required functionality of all Providers on a wasmCloud lattice.
+            ///
+            /// This implementation is a stub and must be filled out by implementers
+            #allow_lints
+            #[#async_trait_path::async_trait]
+            impl #impl_generics #sdk_path::ProviderHandler for #impl_struct_name #ty_generics #where_clause {
+                async fn put_link(&self, ld: &#sdk_path::core::LinkDefinition) -> bool {
+                    #put_link_tokens
+                }
+
+                async fn delete_link(&self, actor_id: &str) {
+                    self._delete_link(actor_id)#await_tokens
+                }
+
+                async fn shutdown(&self) {
+                    self._shutdown()#await_tokens
+                }
+
+                async fn health_request(
+                    &self,
+                    arg: &#sdk_path::core::HealthCheckRequest,
+                ) -> #sdk_path::core::HealthCheckResponse {
+                    self._health_request(arg).await
+                }
+            }
+
+            #fallback_traits_tokens
+
+            /// Given the implementation of ProviderHandler and MessageDispatch,
+            /// the implementation for your struct is a guaranteed
+            #allow_lints
+            impl #impl_generics #sdk_path::Provider for #impl_struct_name #ty_generics #where_clause {}
+        )
+    }
+
+    /// Build the items routing invocations to the methods of every interface: the enumeration of the operations
+    /// the provider handles, the MessageDispatch impl (unless the provider implements dispatch itself) and
+    /// the free function that mirrors it
+    fn build_dispatch(&self, iface_code: &InterfaceCode) -> proc_macro2::TokenStream {
+        let operation_tokens = self.build_operation_enum(iface_code);
+        let supported_operations_tokens = self.build_supported_operations(iface_code);
+        let dispatch_tokens = self.build_dispatch_impl(&iface_code.dispatch_arms);
+        let dispatch_fn_tokens = self.build_dispatch_fn();
+        quote::quote!(
+            #operation_tokens
+
+            #supported_operations_tokens
+
+            #dispatch_tokens
+
+            #dispatch_fn_tokens
+        )
+    }
+
+    /// Build an enumeration of the operations (lattice methods) the provider handles, which dispatch matches on
+    /// (method names are unique across interfaces, so variants are named after the methods), unless the provider
+    /// implements dispatch itself
+    fn build_operation_enum(&self, iface_code: &InterfaceCode) -> proc_macro2::TokenStream {
+        if self.provider_opts.custom_dispatch {
+            return proc_macro2::TokenStream::new();
+        }
+        let Self {
+            sdk_path,
+            allow_lints,
+            ..
+        } = self;
+        let InterfaceCode {
+            operation_variants,
+            operation_names,
+            operation_cfgs,
+            ..
+        } = iface_code;
+        quote::quote!(
+        /// Operations handled by the provider, one per method received over the lattice
+        #allow_lints
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum Operation {
+            #(
+                #operation_cfgs
+                #[doc = #operation_names]
+                #operation_variants,
+            )*
+        }
+
+        #allow_lints
+        impl Operation {
+            /// Every operation handled by the provider
+            pub const ALL: &'static [Operation] = &[#(#operation_cfgs Operation::#operation_variants),*];
+
+            /// Names of the methods of every operation handled by the provider, in the order of [`Operation::ALL`]
+            pub const NAMES: &'static [&'static str] = &[#(#operation_cfgs #operation_names),*];
+
+            /// Name of the method that invocations of the operation are received with
+            pub fn as_str(&self) -> &'static str {
+                match *self {
+                    #(#operation_cfgs Operation::#operation_variants => #operation_names,)*
+                }
+            }
+        }
+
+        #allow_lints
+        impl ::core::str::FromStr for Operation {
+            type Err = #sdk_path::error::InvocationError;
+
+            fn from_str(method: &str) -> Result<Self, Self::Err> {
+                match method {
+                    #(#operation_cfgs #operation_names => Ok(Operation::#operation_variants),)*
+                    _ => Err(#sdk_path::error::InvocationError::Malformed(format!(
+                        "Invalid method name {method}",
+                    ))),
+                }
+            }
+        }
+
+        #allow_lints
+        impl ::core::fmt::Display for Operation {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+        )
+    }
+
+    /// Report the operations the provider handles on the provider itself, so they are available wherever the
+    /// provider is (ex. to the provider's other modules, or code negotiating with the host)
+    fn build_supported_operations(&self, iface_code: &InterfaceCode) -> proc_macro2::TokenStream {
+        let Self {
+            impl_struct_name,
+            allow_lints,
+            ..
+        } = self;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let InterfaceCode {
+            operation_names,
+            operation_cfgs,
+            ..
+        } = iface_code;
+
+        // The const is spanned to the provider, so that a clash with an associated item the provider defines
+        // itself is reported at the provider, rather than at the whole `generate!` invocation
+        let supported_operations = Ident::new("SUPPORTED_OPERATIONS", impl_struct_name.span());
+        quote::quote!(
+            #allow_lints
+            impl #impl_generics #impl_struct_name #ty_generics #where_clause {
+                /// Names of the methods the provider handles, for reporting its capabilities
+                /// (ex. in responses to host queries, or to verify the operations of a deployed provider)
+                pub const #supported_operations: &'static [&'static str] = &[#(#operation_cfgs #operation_names),*];
+            }
+        )
+    }
+
+    /// Build the single MessageDispatch impl that routes to methods of all interfaces,
+    /// unless the provider implements MessageDispatch itself
+    fn build_dispatch_impl(
+        &self,
+        dispatch_arms: &proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let Self {
+            impl_struct_name,
+            provider_opts,
+            sdk_path,
+            async_trait_path,
+            allow_lints,
+            ..
+        } = self;
+        if provider_opts.custom_dispatch {
+            return proc_macro2::TokenStream::new();
+        }
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+
+        // Remap incoming method names before they are matched, if a rewrite function was provided
+        let method_rewrite_tokens = match &provider_opts.method_rewrite {
+            Some(rewrite) => quote::quote!(
+                let method: String = #rewrite(&method).into();
+            ),
+            None => proc_macro2::TokenStream::new(),
+        };
+
+        quote::quote!(
+            /// MessageDispatch ensures that your provider can receive and
+            /// process messages sent to it over the lattice
+            ///
+            /// This implementation is a stub and must be filled out by implementers
+            #allow_lints
+            #[#async_trait_path::async_trait]
+            impl #impl_generics #sdk_path::MessageDispatch for #impl_struct_name #ty_generics #where_clause {
+                #[cfg_attr(
+                    feature = "otel",
+                    ::tracing::instrument(
+                        skip(self, ctx, body),
+                        fields(method = %method, actor = ?ctx.actor, interface = ::tracing::field::Empty),
+                    )
+                )]
+                async fn dispatch<'a>(
+                    &'a self,
+                    ctx: #sdk_path::Context,
+                    method: String,
+                    body: std::borrow::Cow<'a, [u8]>,
+                ) -> Result<Vec<u8>, #sdk_path::error::ProviderInvocationError> {
+                    // Continue the trace of the sender of the invocation
+                    #[cfg(feature = "otel")]
+                    {
+                        use ::tracing_opentelemetry::OpenTelemetrySpanExt;
+                        let parent = ::opentelemetry::global::get_text_map_propagator(|p| p.extract(&ctx.tracing));
+                        ::tracing::Span::current().set_parent(parent);
+                    }
+
+                    #method_rewrite_tokens
+                    // Hooks see every invocation, including those of methods the provider does not handle
+                    self._before_dispatch(&ctx, &method).await?;
+                    let hook_ctx = ctx.clone();
+                    let result = match method.parse::<Operation>() {
+                        Ok(operation) => {
+                            async move {
+                                match operation {
+                                    #dispatch_arms
+                                }
+                            }
+                            .await
+                        }
+                        Err(e) => Err(e.into()),
+                    };
+                    self._after_dispatch(&hook_ctx, &method, &result).await;
+                    result
+                }
+            }
+        )
+    }
+
+    /// Build a free function that mirrors dispatch, for calling from outside the MessageDispatch trait
+    /// (ex. from a component export)
+    fn build_dispatch_fn(&self) -> proc_macro2::TokenStream {
+        let Self {
+            impl_struct_name,
+            provider_opts,
+            sdk_path,
+            allow_lints,
+            ..
+        } = self;
+        if !provider_opts.dispatch_fn {
+            return proc_macro2::TokenStream::new();
+        }
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let dispatch_fn_name = dispatch_fn_name(impl_struct_name, provider_opts);
+        quote::quote!(
+            /// Handle an invocation received for the provider, exactly as its `MessageDispatch::dispatch` would
+            #allow_lints
+            async fn #dispatch_fn_name #impl_generics (
+                provider: &#impl_struct_name #ty_generics,
+                ctx: #sdk_path::Context,
+                method: String,
+                body: &[u8],
+            ) -> Result<Vec<u8>, #sdk_path::error::ProviderInvocationError>
+            #where_clause
+            {
+                #sdk_path::MessageDispatch::dispatch(
+                    provider,
+                    ctx,
+                    method,
+                    ::std::borrow::Cow::Borrowed(body),
+                )
+                .await
+            }
+        )
+    }
+
+    /// Build a helper that runs the provider, so a provider's `main` doesn't have to wire up the SDK's runtime
+    ///
+    /// The provider is created with its `Default` impl, which is asserted separately so that a missing impl
+    /// produces an error pointing at the provider that names the contract, rather than one inside `start()`
+    fn build_entrypoint(&self) -> proc_macro2::TokenStream {
+        let Self {
+            impl_struct_name,
+            provider_opts,
+            sdk_path,
+            allow_lints,
+            ..
+        } = self;
+        if !provider_opts.entrypoint {
+            return proc_macro2::TokenStream::new();
+        }
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let startable_assertion = quote::quote_spanned!(impl_struct_name.span()=>
+            provider_must_implement_default_to_be_started::<#impl_struct_name #ty_generics>()
+        );
+        quote::quote!(
+            #allow_lints
+            const _: () = {
+                fn provider_must_implement_default_to_be_started<P: ::core::default::Default>() {}
+                fn assert_startable #impl_generics () #where_clause {
+                    #startable_assertion
+                }
+            };
+
+            #allow_lints
+            impl #impl_generics #impl_struct_name #ty_generics #where_clause {
+                /// Create the provider (with its `Default` impl) and run it, performing the handshake
+                /// with the wasmCloud host and handling invocations until the provider is shut down
+                ///
+                /// Providers that cannot be created with `Default` (ex. as they require arguments) should
+                /// instead be passed to `wasmcloud_provider_sdk::run_provider` after being created
+                pub async fn start(
+                    friendly_name: Option<String>,
+                ) -> #sdk_path::error::ProviderResult<()> {
+                    #sdk_path::run_provider(
+                        <Self as ::core::default::Default>::default(),
+                        friendly_name,
+                    )
+                    .await
+                }
+            }
+        )
+    }
+
+    /// Build the items describing the provider's methods and interfaces: the skeleton of the methods it must
+    /// implement, the description of its contract for tooling, and the checks that it implements every method
+    fn build_metadata(
+        &self,
+        methods_by_iface: &HashMap<WitInterfaceName, Vec<LatticeMethod>>,
+        iface_paths_by_name: &HashMap<WitInterfaceName, WitInterfacePath>,
+    ) -> proc_macro2::TokenStream {
+        let Self {
+            impl_struct_name,
+            nested_impl_struct_name,
+            provider_opts,
+            sdk_path,
+            allow_lints,
+            claims_arg,
+            ..
+        } = self;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+
+        // Build the source of an impl block with every method the provider must implement, for copying into provider code
+        let method_skeleton_tokens = if provider_opts.method_skeleton {
+            let skeleton = build_method_skeleton(
+                impl_struct_name,
+                self.generics,
+                methods_by_iface,
+                provider_opts
+                    .config
+                    .clone()
+                    .or_else(|| {
+                        provider_opts
+                            .config_map
+                            .then(|| syn::parse_quote!(HashMap<String, String>))
+                    })
+                    .as_ref(),
+                provider_opts.claims.as_ref(),
+                provider_opts.mode,
+                sdk_path,
+            );
+            quote::quote!(
+                /// Skeleton of the methods the provider must implement, which can be copied into
+                /// the provider's code (ex. by printing it from a test) and filled out
+                #allow_lints
+                pub const PROVIDER_METHOD_SKELETON: &str = #skeleton;
+            )
+        } else {
+            proc_macro2::TokenStream::new()
+        };
+
+        // Build a description of the handled interfaces and their operations, for use by tooling
+        let contract_metadata_tokens = if provider_opts.contract_metadata {
+            let contract = build_contract_metadata(iface_paths_by_name.values(), methods_by_iface);
+            quote::quote!(
+                /// JSON description of the interfaces handled by the provider and their operations
+                /// (ex. `{"interfaces":[{"namespace":"wasmcloud","package":"keyvalue","version":null,
+                /// "interface":"key-value","operations":["wasmcloud:keyvalue/key-value.get"]}]}`)
+                #allow_lints
+                pub const PROVIDER_CONTRACT: &str = #contract;
+            )
+        } else {
+            proc_macro2::TokenStream::new()
+        };
+
+        // Check that the provider implements every method the generated traits delegate to. The traits are not
+        // in scope in the nested module, so a missing method is reported (at the provider) as missing, rather than
+        // the trait impl's `self.<method>(..)` resolving to the trait method itself and recursing forever
+        let method_check_fns = methods_by_iface
+            .iter()
+            .flat_map(|(wit_iface_name, methods)| {
+                let iface_cfg = interface_cfg(&iface_paths_by_name[wit_iface_name], provider_opts);
+                methods.iter().map(move |m| (iface_cfg.clone(), m))
+            })
+            .map(|(iface_cfg, m)| {
+                let func_name = &m.func_name;
+                let mut provider_func_name = m.func_name.clone();
+                provider_func_name.set_span(impl_struct_name.span());
+                let args = &m.invocation_args;
+                let expected = format!(
+                    "Expected provider method: `{}`",
+                    method_signature(m, provider_opts.claims.as_ref(), provider_opts.mode, sdk_path)
+                );
+                quote::quote!(
+                    #iface_cfg
+                    #[doc = #expected]
+                    fn #func_name #impl_generics (
+                        provider: &#nested_impl_struct_name #ty_generics,
+                        ctx: #sdk_path::Context,
+                    ) #where_clause {
+                        let _ = |#claims_arg #(#args),*| provider.#provider_func_name(ctx, #claims_arg #(#args),*);
+                    }
+                )
+            })
+            .collect::<Vec<proc_macro2::TokenStream>>();
+
+        // The bounds of a generic provider's params are written for the provider's module, so its items are imported,
+        // save for the generated traits (including the local variants produced by trait_variant), which are shadowed
+        // so their methods can't stand in for the provider's
+        let generic_provider_imports = if self.generics.params.is_empty() {
+            proc_macro2::TokenStream::new()
+        } else {
+            let iface_traits = methods_by_iface
+                .keys()
+                .map(|name| format_ident!("{}{}", name, provider_opts.trait_suffix))
+                .flat_map(|name| {
+                    let local_name = (provider_opts.trait_style == TraitStyle::TraitVariant
+                        && provider_opts.mode != MethodMode::Sync)
+                        .then(|| format_ident!("Local{}", name));
+                    std::iter::once(name).chain(local_name)
+                });
+            quote::quote!(
+                use super::*;
+                #(struct #iface_traits;)*
+            )
+        };
+
+        quote::quote!(
+            #method_skeleton_tokens
+
+            #contract_metadata_tokens
+
+            /// Checks that the provider implements the methods of every interface it handles
+            #allow_lints
+            mod provider_method_checks {
+                #generic_provider_imports
+                #(#method_check_fns)*
+            }
+        )
+    }
+
+    /// Gather the semaphores that limit method concurrency into a module
+    fn build_concurrency_module(&self, iface_code: &InterfaceCode) -> proc_macro2::TokenStream {
+        let concurrency_tokens = &iface_code.concurrency_tokens;
+        if concurrency_tokens.is_empty() {
+            return proc_macro2::TokenStream::new();
+        }
+        let allow_lints = &self.allow_lints;
+        quote::quote!(
+            /// Semaphores limiting the number of concurrent executions of specific methods,
+            /// shared by every instance of the provider
+            #allow_lints
+            mod method_concurrency {
+                #concurrency_tokens
+            }
+        )
+    }
+
+    /// Build the (de)serialization functions for wire formats other than the SDK's own, or the trait
+    /// implemented by the codec the provider uses instead
+    fn build_wire_format_module(&self) -> proc_macro2::TokenStream {
+        let Self {
+            provider_opts,
+            sdk_path,
+            serde_path,
+            allow_lints,
+            ..
+        } = self;
+        match provider_opts.wire_format {
+            _ if provider_opts.codec.is_some() => quote::quote!(
+                /// (De)serialization of invocations and their results, implemented by the codec the provider
+                /// is generated with (ex. to compress, encrypt or use a custom format)
+                #allow_lints
+                pub trait InvocationCodec {
+                    /// Encode an invocation or result into the bytes sent over the lattice
+                    fn encode<T: #serde_path::Serialize + ?Sized>(
+                        data: &T,
+                    ) -> Result<Vec<u8>, #sdk_path::error::InvocationError>;
+
+                    /// Decode an invocation or result from the bytes received over the lattice
+                    fn decode<'de, T: #serde_path::Deserialize<'de>>(
+                        buf: &'de [u8],
+                    ) -> Result<T, #sdk_path::error::InvocationError>;
+                }
+            ),
+            WireFormat::Sdk => proc_macro2::TokenStream::new(),
+            WireFormat::Msgpack | WireFormat::Json => {
+                let (to_vec, from_slice) = if provider_opts.wire_format == WireFormat::Msgpack {
+                    (
+                        quote::quote!(::rmp_serde::to_vec_named),
+                        quote::quote!(::rmp_serde::from_slice),
+                    )
+                } else {
+                    (
+                        quote::quote!(::serde_json::to_vec),
+                        quote::quote!(::serde_json::from_slice),
+                    )
+                };
+                quote::quote!(
+                    /// (De)serialization of invocations and their results in the wire format selected for the provider
+                    #allow_lints
+                    mod wire_format {
+                        pub(super) fn serialize<T: #serde_path::Serialize + ?Sized>(
+                            data: &T,
+                        ) -> Result<Vec<u8>, #sdk_path::error::InvocationError> {
+                            #to_vec(data).map_err(|e| {
+                                #sdk_path::error::InvocationError::Ser(e.to_string())
+                            })
+                        }
+
+                        pub(super) fn deserialize<'de, T: #serde_path::Deserialize<'de>>(
+                            buf: &'de [u8],
+                        ) -> Result<T, #sdk_path::error::InvocationError> {
+                            #from_slice(buf).map_err(|e| {
+                                #sdk_path::error::InvocationError::Deser(e.to_string())
+                            })
+                        }
+                    }
+                )
+            }
+        }
+    }
+
+    /// Gather the dispatch benchmarks into a module that is only built for benchmarking
+    fn build_bench_module(&self, iface_code: &InterfaceCode) -> proc_macro2::TokenStream {
+        if !self.provider_opts.bench_harness {
+            return proc_macro2::TokenStream::new();
+        }
+        let allow_lints = &self.allow_lints;
+        let bench_tokens = &iface_code.bench_tokens;
+        let bench_cfg = self
+            .provider_opts
+            .bench_cfg
+            .clone()
+            .unwrap_or_else(|| syn::parse_quote!(feature = "bench"));
+        quote::quote!(
+            /// Criterion benchmarks for measuring dispatch overhead of each lattice method
+            #allow_lints
+            #[cfg(#bench_cfg)]
+            mod dispatch_bench {
+                #bench_tokens
+            }
+        )
+    }
+
+    /// Gather the round-trip tests into a module that is only built for testing
+    fn build_round_trip_tests_module(
+        &self,
+        iface_code: &InterfaceCode,
+    ) -> proc_macro2::TokenStream {
+        if !self.provider_opts.generate_tests {
+            return proc_macro2::TokenStream::new();
+        }
+        let allow_lints = &self.allow_lints;
+        let round_trip_test_tokens = &iface_code.round_trip_test_tokens;
+        quote::quote!(
+            /// Serde round-trip tests of the invocations received over the lattice
+            #allow_lints
+            #[cfg(test)]
+            mod invocation_round_trip_tests {
+                #round_trip_test_tokens
+            }
+        )
+    }
+}
+
+/// Build the parameters of the methods of an interface following `ctx` (in the per-interface trait and clients),
+/// which are absent for functions without arguments
+fn build_trait_fn_params(methods: &[LatticeMethod]) -> Vec<proc_macro2::TokenStream> {
+    methods
+        .iter()
+        .map(|m| {
+            if m.invocation_args.is_empty() {
+                proc_macro2::TokenStream::new()
+            } else {
+                let struct_members = &m.struct_members;
+                quote::quote!(#struct_members,)
+            }
+        })
+        .collect()
+}
+
+/// Attribute allowing lints on a generated item, which the provider has no control over
+/// (ex. unused functions imported from the host, or wit-bindgen's own style)
+///
+/// Only the generated items themselves are annotated, so lints in the provider's own code are unaffected.
+fn allow_lints_attr() -> Attribute {
+    syn::parse_quote!(#[allow(unused, clippy::all)])
+}
+
+/// Trait providing fallbacks for optional methods, which providers may define alongside their other methods
+struct FallbackTrait {
+    /// Name of the trait (ex. `DefaultHealthRequest`)
+    name: Ident,
+    /// Doc comment of the trait
+    doc: &'static str,
+    /// Methods of the trait, with the fallback implementations
+    methods: proc_macro2::TokenStream,
+}
+
+/// Build a module with the given fallback traits, which are implemented for the provider and brought into scope
+/// (anonymously, so they don't collide with the provider's own items) where `generate!` is invoked
+///
+/// As inherent methods take precedence over trait methods, methods defined on the provider itself are always
+/// called instead of the fallbacks. The module is named after the provider (ex. `kv_provider_fallbacks`), so
+/// that providers generated in the same module each have their own.
+fn build_fallback_traits(
+    impl_struct_name: &Path,
+    generics: &syn::Generics,
+    fallback_traits: &[FallbackTrait],
+    async_trait_path: &Path,
+    nested_async_trait_path: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let allow_lints = allow_lints_attr();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let provider_name = impl_struct_name
+        .segments
+        .last()
+        .map(|s| s.ident.unraw().to_string())
+        .unwrap_or_default();
+    let module = format_ident!("{}_fallbacks", provider_name.to_snake_case());
+    let names = fallback_traits
+        .iter()
+        .map(|t| &t.name)
+        .collect::<Vec<&Ident>>();
+    let docs = fallback_traits.iter().map(|t| t.doc);
+    let methods = fallback_traits.iter().map(|t| &t.methods);
+    quote::quote!(
+        /// Fallbacks for the optional methods of the provider, used when the provider does not define them itself
+        #allow_lints
+        mod #module {
+            #(
+                #[doc = #docs]
+                #[#nested_async_trait_path::async_trait]
+                pub(super) trait #names {
+                    #methods
+                }
+            )*
+        }
+
+        #(
+            #allow_lints
+            use #module::#names as _;
+
+            #allow_lints
+            #[#async_trait_path::async_trait]
+            impl #impl_generics #module::#names for #impl_struct_name #ty_generics #where_clause {}
+        )*
+    )
+}
+
+/// Allow lints on the items generated by wit-bindgen
+fn allow_generated_lints(items: &mut [Item]) {
+    let allow = allow_lints_attr();
+    for attrs in items.iter_mut().filter_map(item_attrs_mut) {
+        attrs.insert(0, allow.clone());
+    }
+}
+
+/// Get the attributes of an item, if it is a kind of item that can have them
+fn item_attrs_mut(item: &mut Item) -> Option<&mut Vec<Attribute>> {
+    match item {
+        Item::Const(i) => Some(&mut i.attrs),
+        Item::Enum(i) => Some(&mut i.attrs),
+        Item::Fn(i) => Some(&mut i.attrs),
+        Item::Impl(i) => Some(&mut i.attrs),
+        Item::Macro(i) => Some(&mut i.attrs),
+        Item::Mod(i) => Some(&mut i.attrs),
+        Item::Static(i) => Some(&mut i.attrs),
+        Item::Struct(i) => Some(&mut i.attrs),
+        Item::Trait(i) => Some(&mut i.attrs),
+        Item::Type(i) => Some(&mut i.attrs),
+        Item::Union(i) => Some(&mut i.attrs),
+        Item::Use(i) => Some(&mut i.attrs),
+        _ => None,
+    }
+}
+
+/// Build the `#[cfg(feature = "...")]` attribute that the generated code of an interface is gated behind,
+/// if the interface was mapped to a feature with `interface_features`
+fn interface_cfg(iface_path: &WitInterfacePath, provider_opts: &ProviderOpts) -> Option<Attribute> {
+    let features = &provider_opts.interface_features;
+    features
+        .get(&format!("{}/{}", iface_path.package, iface_path.iface))
+        .or_else(|| features.get(&iface_path.iface))
+        .map(|feature| syn::parse_quote!(#[cfg(feature = #feature)]))
+}
+
+/// Ensure that every interface mapped to a feature with `interface_features` is an interface with functions,
+/// as a misspelled name would otherwise leave the interface ungated
+fn check_interface_features<'a>(
+    iface_paths: impl Iterator<Item = &'a WitInterfacePath>,
+    provider_opts: &ProviderOpts,
+) -> syn::Result<()> {
+    let iface_paths = iface_paths.collect::<Vec<&WitInterfacePath>>();
+    let found = iface_paths
+        .iter()
+        .flat_map(|p| [p.iface.clone(), format!("{}/{}", p.package, p.iface)])
+        .collect::<HashSet<String>>();
+    let to_kebab = |key: &str| {
+        key.split('/')
+            .map(|part| part.to_kebab_case())
+            .collect::<Vec<String>>()
+            .join("/")
+    };
+    let mut unknown = provider_opts
+        .interface_features
+        .keys()
+        .filter(|k| !found.contains(k.as_str()))
+        .map(|k| to_kebab(k.as_str()))
+        .collect::<Vec<String>>();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    unknown.sort();
+    let mut expected = iface_paths
+        .iter()
+        .map(|p| p.iface.to_kebab_case())
+        .collect::<HashSet<String>>()
+        .into_iter()
+        .collect::<Vec<String>>();
+    expected.sort();
+    Err(syn::Error::new(
+        Span::call_site(),
+        format!(
+            "unknown interfaces [{}] in interface_features; expected one of [{}] (optionally qualified by package, ex. `<package>/<interface>`)",
+            unknown.join(", "),
+            expected.join(", "),
+        ),
+    ))
+}
+
+/// Add a `cfg` attribute (if any) to each of the items in the given tokens
+fn cfg_gate_items(
+    tokens: proc_macro2::TokenStream,
+    cfg: Option<&Attribute>,
+) -> proc_macro2::TokenStream {
+    let Some(cfg) = cfg else {
+        return tokens;
+    };
+    let mut file = match syn::parse2::<syn::File>(tokens.clone()) {
+        Ok(file) => file,
+        // Tokens that don't parse are left as-is, so the error is reported when the output is parsed
+        Err(_) => return tokens,
+    };
+    for attrs in file.items.iter_mut().filter_map(item_attrs_mut) {
+        attrs.insert(0, cfg.clone());
+    }
+    file.into_token_stream()
+}
+
+/// Write the code generated for an interface to a file in `OUT_DIR`, returning an `include!` of that file
+///
+/// Files are named after the provider, interface and a hash of their contents, so that names are
+/// deterministic and unique, and are only written when missing, so unchanged interfaces don't trigger rebuilds
+fn include_from_out_dir(
+    impl_struct_name: &Path,
+    wit_iface_name: &str,
+    tokens: proc_macro2::TokenStream,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let out_dir = std::env::var("OUT_DIR").map_err(|_| {
+        syn::Error::new(
+            Span::call_site(),
+            "split_output requires OUT_DIR to be set, which requires the provider to have a build script \
+             (ex. a build.rs containing `fn main() {}`)",
+        )
+    })?;
+
+    // Included items already allow lints, like inlined ones
+    let contents = render_expansion(&tokens);
+
+    let provider_name = impl_struct_name
+        .segments
+        .last()
+        .map(|s| s.ident.unraw().to_string())
+        .unwrap_or_default();
+    let file_name = format!(
+        "wasmcloud_{}_{}_{:016x}.rs",
+        provider_name.to_snake_case(),
+        wit_iface_name.to_snake_case(),
+        fnv1a_hash(contents.as_bytes())
+    );
+    let path = std::path::Path::new(&out_dir).join(&file_name);
+    if !path.exists() {
+        std::fs::write(&path, contents).map_err(|e| {
+            syn::Error::new(
+                Span::call_site(),
+                format!(
+                    "failed to write generated code to [{}]: {e}",
+                    path.display()
+                ),
+            )
+        })?;
+    }
+
+    let include_path = format!("/{file_name}");
+    let allow_lints = allow_lints_attr();
+    Ok(quote::quote!(
+        #allow_lints
+        include!(concat!(env!("OUT_DIR"), #include_path));
+    ))
+}
+
+/// Name of the free function that mirrors dispatch, which defaults to `handle_<provider>`
+fn dispatch_fn_name(impl_struct_name: &Path, provider_opts: &ProviderOpts) -> Ident {
+    provider_opts.dispatch_fn_name.clone().unwrap_or_else(|| {
+        let provider_name = impl_struct_name
+            .segments
+            .last()
+            .map(|s| s.ident.unraw().to_string())
+            .unwrap_or_default();
+        format_ident!("handle_{}", provider_name.to_snake_case())
+    })
+}
+
+/// Hash bytes with 64-bit FNV-1a, which (unlike [`std::hash::DefaultHasher`]) is stable across Rust versions
+fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Build the source of an impl block containing every method a provider must implement, with `todo!()` bodies
+fn build_method_skeleton(
+    impl_struct_name: &Path,
+    generics: &syn::Generics,
+    methods_by_iface: &HashMap<WitInterfaceName, Vec<LatticeMethod>>,
+    config: Option<&syn::Type>,
+    claims: Option<&Path>,
+    mode: MethodMode,
+    sdk_path: &Path,
+) -> String {
+    let sdk = rendered_sdk_path(sdk_path);
+    let config_param = config
+        .map(|c| format!("config: {}, ", pretty_tokens(c.to_token_stream())))
+        .unwrap_or_default();
+    // Lifecycle methods are blocking in sync mode, just like the methods of the interfaces
+    let asyncness = match mode {
+        MethodMode::Async => "async ",
+        MethodMode::Sync => "",
+    };
+    let mut skeleton = format!(
+        "{} {{\n{}",
+        {
+            let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+            pretty_tokens(quote::quote!(impl #impl_generics #impl_struct_name #ty_generics #where_clause))
+        },
+        [
+            format!("    {asyncness}fn _put_link(&self, {config_param}ld: &{sdk}::core::LinkDefinition) -> bool {{\n        todo!()\n    }}\n"),
+            format!("    {asyncness}fn _delete_link(&self, actor_id: &str) {{\n        todo!()\n    }}\n"),
+            format!("    {asyncness}fn _shutdown(&self) {{\n        todo!()\n    }}\n"),
+        ]
+        .join("\n")
+    );
+
+    let mut iface_names = methods_by_iface.keys().collect::<Vec<&WitInterfaceName>>();
+    iface_names.sort();
+    for iface_name in iface_names {
+        for m in methods_by_iface[iface_name].iter() {
+            skeleton.push_str(&format!(
+                "\n    // {iface_name}\n    {} {{\n        todo!()\n    }}\n",
+                method_signature(m, claims, mode, sdk_path),
+            ));
+        }
+    }
+    skeleton.push_str("}\n");
+    skeleton
+}
+
+/// Build a JSON description of the given interfaces and the lattice methods of each, sorted so output is stable
+///
+/// Names of WIT namespaces, packages, interfaces and functions cannot contain characters that need escaping in JSON
+fn build_contract_metadata<'a>(
+    iface_paths: impl Iterator<Item = &'a WitInterfacePath>,
+    methods_by_iface: &HashMap<WitInterfaceName, Vec<LatticeMethod>>,
+) -> String {
+    let mut iface_paths = iface_paths.collect::<Vec<&WitInterfacePath>>();
+    iface_paths.sort_by_key(|p| (&p.ns, &p.package, &p.version, &p.iface));
+    let iface_names = unique_interface_names(iface_paths.iter().copied());
+    let interfaces = iface_paths
+        .into_iter()
+        .map(|p| {
+            let operations = methods_by_iface
+                .get(&iface_names[p])
+                .into_iter()
+                .flatten()
+                .map(|m| format!("\"{}\"", m.lattice_method_name.value()))
+                .collect::<Vec<String>>();
+            format!(
+                "{{\"namespace\":\"{}\",\"package\":\"{}\",\"version\":{},\"interface\":\"{}\",\"operations\":[{}]}}",
+                p.ns.to_kebab_case(),
+                p.package.to_kebab_case(),
+                p.version
+                    .as_ref()
+                    .map_or_else(|| "null".to_string(), |v| format!("\"{}\"", v.replace('_', "."))),
+                p.iface.to_kebab_case(),
+                operations.join(","),
+            )
+        })
+        .collect::<Vec<String>>();
+    format!("{{\"interfaces\":[{}]}}", interfaces.join(","))
+}
+
+/// Render the signature of the inherent method a provider must implement for a lattice method
+/// (ex. `async fn get(&self, ctx: wasmcloud_provider_sdk::Context, key: String) -> Option<String>`)
+fn method_signature(
+    m: &LatticeMethod,
+    claims: Option<&Path>,
+    mode: MethodMode,
+    sdk_path: &Path,
+) -> String {
+    let sdk = rendered_sdk_path(sdk_path);
+    let claims_param = claims
+        .map(|c| format!(", claims: {}", pretty_tokens(c.to_token_stream())))
+        .unwrap_or_default();
+    let params = if m.invocation_args.is_empty() {
+        String::new()
+    } else {
+        format!(", {}", pretty_tokens(m.struct_members.clone()))
+    };
+    format!(
+        "{}fn {}(&self, ctx: {sdk}::Context{claims_param}{params}){}",
+        match mode {
+            MethodMode::Async => "async ",
+            MethodMode::Sync => "",
+        },
+        m.func_name,
+        match &m.invocation_return {
+            ReturnType::Default => String::new(),
+            ReturnType::Type(_, ty) => format!(" -> {}", pretty_tokens(ty.to_token_stream())),
+        },
+    )
+}
+
+/// Render the path to the SDK for use in provider source, without the leading `::` of the default
+/// (ex. `wasmcloud_provider_sdk` or `crate::sdk`)
+fn rendered_sdk_path(sdk_path: &Path) -> String {
+    pretty_tokens(sdk_path.to_token_stream())
+        .trim_start_matches("::")
+        .to_string()
+}
+
+/// Render tokens as source, without the spacing between tokens that `TokenStream::to_string` adds
+/// around paths, generics and punctuation (ex. `Option<String>` rather than `Option < String >`)
+fn pretty_tokens(ts: proc_macro2::TokenStream) -> String {
+    [
+        (" :: ", "::"),
+        (":: ", "::"),
+        (" < ", "<"),
+        ("< ", "<"),
+        (" >", ">"),
+        (" ,", ","),
+        (" : ", ": "),
+        ("& ", "&"),
+        ("( ", "("),
+        (" )", ")"),
+    ]
+    .iter()
+    .fold(ts.to_string(), |s, (from, to)| s.replace(from, to))
+}
+
+/// Find the comma ending the provider argument, skipping any between the provider's generic params
+/// or the predicates of its where clause
+fn find_provider_arg_end(tokens: &[TokenTree]) -> Option<usize> {
+    let mut angle_depth = 0usize;
+    let mut in_where_clause = false;
+    let mut prev = None;
+    for (idx, tt) in tokens.iter().enumerate() {
+        let p = match tt {
+            TokenTree::Punct(p) => p,
+            TokenTree::Ident(i) if angle_depth == 0 && i == "where" => {
+                in_where_clause = true;
+                prev = None;
+                continue;
+            }
+            _ => {
+                prev = None;
+                continue;
+            }
+        };
+        match p.as_char() {
+            '<' => angle_depth += 1,
+            // `->` (ex. in a `Fn() -> T` bound) doesn't close a generic param list
+            '>' if prev != Some('-') => angle_depth = angle_depth.saturating_sub(1),
+            // Predicates are separated by commas too, so the clause only ends where the wit-bindgen args start
+            // (ex. `{ .. }`, `"world"` or `in "path"`)
+            ',' if angle_depth == 0
+                && (!in_where_clause
+                    || matches!(
+                        tokens.get(idx + 1),
+                        Some(TokenTree::Group(_) | TokenTree::Literal(_)) | None
+                    )
+                    || matches!(tokens.get(idx + 1), Some(TokenTree::Ident(i)) if i == "in")) =>
+            {
+                return Some(idx)
+            }
+            _ => {}
+        }
+        prev = Some(p.as_char());
+    }
+    None
+}
+
+/// Parse the provider argument: a path to the provider struct, optionally followed by its generic params
+/// (with their bounds) and a where clause, ex. `YourProvider<B: Backend>` or `YourProvider<B> where B: Backend`
+fn parse_provider_arg(input: syn::parse::ParseStream) -> syn::Result<(Path, syn::Generics)> {
+    let path = Path::parse_mod_style(input)?;
+    let mut generics: syn::Generics = input.parse()?;
+    generics.where_clause = input.parse()?;
+    Ok((path, generics))
+}
+
+/// Convert a path that is valid in the provider's module into one that is valid in a module generated inside it
+fn nested_path(path: &Path) -> proc_macro2::TokenStream {
+    match path.segments.first() {
+        // Absolute paths are valid anywhere
+        _ if path.leading_colon.is_some() => path.to_token_stream(),
+        Some(first) if first.ident == "crate" => path.to_token_stream(),
+        // `self` refers to the provider's module, which is `super` of the nested module
+        Some(first) if first.ident == "self" => {
+            let rest = path.segments.iter().skip(1);
+            quote::quote!(super #(::#rest)*)
+        }
+        _ => quote::quote!(super::#path),
+    }
+}
+
+/// Check whether wit-bindgen failed to generate any bindings, without reporting an error
+///
+/// This happens when tokens just don't get generated (ex. when exported world does not match
+/// package (as in package <ns>/<package>)), in which case the output is empty save for the
+/// dummy `include_str!` consts
+fn is_empty_bindgen_output(items: &[Item]) -> bool {
+    items.iter().all(|item| matches!(item, Item::Const(_)))
+}
+
+/// Check whether an item is an invocation of `compile_error!`
+fn is_compile_error(item: &Item) -> bool {
+    match item {
+        Item::Macro(m) => m
+            .mac
+            .path
+            .segments
+            .last()
+            .is_some_and(|s| s.ident == "compile_error"),
+        _ => false,
+    }
+}
+
+/// Check the wit-bindgen args for obvious malformations (i.e. stray commas)
+///
+/// A single trailing comma (ex. `generate!(YourProvider, "world",)`) is allowed, as in regular Rust,
+/// and must be stripped before the args are handed to wit-bindgen
+fn validate_bindgen_args(rest: &[TokenTree]) -> syn::Result<()> {
+    let is_comma = |tt: &TokenTree| matches!(tt, TokenTree::Punct(p) if p.as_char() == ',');
+
+    // ex. generate!(YourProvider, , "world")
+    if let Some(tt) = rest.first().filter(|tt| is_comma(tt)) {
+        return Err(syn::Error::new(
+            tt.span(),
+            "unexpected `,`, expected wit-bindgen args after the provider struct name",
+        ));
+    }
+
+    // ex. generate!(YourProvider, "world",, ...)
+    if let Some(pair) = rest.windows(2).find(|w| is_comma(&w[0]) && is_comma(&w[1])) {
+        return Err(syn::Error::new(
+            pair[1].span(),
+            "unexpected `,`, wit-bindgen args must not contain consecutive commas",
+        ));
+    }
+
+    Ok(())
+}
+
+/// A struct for visiting the output of wit-bindgen
+/// focused around gathering all the important declarations we care about
+#[derive(Default)]
+struct WitBindgenOutputVisitor {
+    /// The detected namespace currently being traversed
+    wit_ns: Option<WitNamespaceName>,
+
+    /// The detected package currently being traversed, without any version suffix
+    wit_package: Option<WitPackageName>,
+
+    /// The version of the detected package currently being traversed, if it is versioned
+    wit_package_version: Option<WitPackageVersion>,
+
+    /// Versions of the resolved versioned packages, keyed by their namespace and package module names
+    package_versions: HashMap<(WitNamespaceName, WitPackageName), WitPackageVersion>,
+
+    /// Name of the module generated for the package currently being traversed,
+    /// which contains the version for versioned packages
+    wit_package_module: Option<String>,
+
+    /// All detected packages, by the namespace they belong to
+    wit_packages: HashMap<WitNamespaceName, HashSet<WitPackageName>>,
+
+    /// Parents of the current module being traversed
+    parents: Vec<Ident>,
+
+    /// Whether the top level module being traversed is the 'exports' module generated by wit-bindgen
+    in_exports_module: bool,
+
+    /// Whether a top level module named `exports` was already traversed, for telling the module of
+    /// a WIT namespace named `exports` apart from the 'exports' module (which wit-bindgen generates after it)
+    seen_exports_named_module: bool,
+
+    /// Structs that were modified and extended to derive Serialize/Deserialize,
+    /// keyed by their full path (ex. `wasmcloud::keyvalue::types::Metadata`)
+    serde_extended_structs: HashMap<String, Punctuated<syn::PathSegment, PathSep>>,
+
+    /// Functions in traits that we'll have to stub eventually
+    import_trait_fns: HashMap<WitInterfacePath, Vec<ItemFn>>,
+
+    /// Names of the owned forms of borrowed records (ex. `BrokerMessageResult` for `BrokerMessageParam<'a>`),
+    /// keyed by the full path of the borrowed form
+    owned_struct_forms: HashMap<String, String>,
+
+    /// Full paths of the structs generated by `bitflags!` for WIT flags, along with the integer type of their bits
+    flags_types: HashMap<String, Ident>,
+
+    /// Paths to the modules that contain the gathered functions of each interface
+    import_module_paths: HashMap<WitInterfacePath, Punctuated<PathSegment, PathSep>>,
+
+    /// Record fields that should be flattened when (de)serialized,
+    /// as (<struct name>, <field name>) pairs
+    flatten_fields: HashSet<(String, String)>,
+
+    /// Module name of the WIT namespace to use, rather than any top level module
+    wit_ns_override: Option<String>,
+
+    /// Module name of the WIT package (without version) to use, rather than any module under the namespace
+    wit_package_override: Option<String>,
+
+    /// Path to the serde crate that derives are pointed at, when it is renamed or re-exported
+    serde_path: Option<Path>,
+
+    /// Whether records fail to deserialize with fields that are not in the WIT
+    deny_unknown_fields: bool,
+
+    /// Whether record fields are (de)serialized with their original WIT names
+    wit_field_names: bool,
+}
+
+impl WitBindgenOutputVisitor {
+    fn is_wit_ns(&self, s: impl AsRef<str>) -> bool {
+        if let Some(v) = &self.wit_ns {
+            v == s.as_ref()
+        } else {
+            false
+        }
+    }
+
+    fn current_module_level(&self) -> usize {
+        self.parents.len()
+    }
+
+    /// Prefix for debug output of the traversal, indented by module level and including the module path
+    /// (ex. `    [lvl 2] [wasmcloud::keyvalue] `)
+    fn debug_location(&self) -> String {
+        let path = self
+            .parents
+            .iter()
+            .map(Ident::to_string)
+            .collect::<Vec<String>>()
+            .join("::");
+        format!(
+            "{}[lvl {}] [{}] ",
+            "  ".repeat(self.current_module_level()),
+            self.current_module_level(),
+            if path.is_empty() { "<root>" } else { &path },
+        )
+    }
+
+    /// Check whether a the current node is directly under the wasm namespace
+    /// Primarily used for detecting the package
+    /// i.e. '<ns>/<package>'
+    fn at_wit_ns_module_child(&self) -> bool {
+        self.parents
+            .last()
+            .is_some_and(|ps| self.is_wit_ns(ps.to_string()))
+    }
+
+    /// Get the path of the current module relative to the given package module, if the current
+    /// module is below it (ex. `[<interface>]` while in `<ns>::<package>::<interface>`)
+    fn package_relative_path(&self, pkg_module: impl AsRef<str>) -> Option<&[Ident]> {
+        match self.parents.as_slice() {
+            [_ns, pkg, rest @ ..] if *pkg == pkg_module.as_ref() && !rest.is_empty() => Some(rest),
+            _ => None,
+        }
+    }
+
+    /// Get the interface that is currently being traversed, if it is inside an imported (non-export) package
+    fn current_import_interface(&self) -> Option<WitInterfacePath> {
+        if self.at_exported_module() {
+            return None;
+        }
+        let pkg = self.wit_package.as_ref()?;
+        let iface_modules = self.package_relative_path(self.wit_package_module.as_ref()?)?;
+
+        // Functions are tracked by the package they belong to, as a world may pull in
+        // interfaces from more than one package, while interfaces nested deeper than
+        // directly under the package (ex. through layered worlds) are keyed by their full path
+        Some(WitInterfacePath {
+            ns: self.wit_ns.clone().unwrap_or_default(),
+            package: pkg.clone(),
+            version: self.wit_package_version.clone(),
+            iface: iface_modules
+                .iter()
+                .map(Ident::to_string)
+                .collect::<Vec<String>>()
+                .join("_"),
+        })
+    }
+
+    /// Save the import path of a type that was extended with serde derives, so that
+    /// references to it can be fully qualified
+    ///
+    /// Types are saved by their full path, as different packages/interfaces may define types with the same name
+    fn save_serde_extended_path(&mut self, ident: &Ident) -> String {
+        let mut import_path = Punctuated::<syn::PathSegment, Token![::]>::new();
+        for p in self.parents.iter() {
+            import_path.push(syn::PathSegment::from(p.clone()));
+        }
+        import_path.push(syn::PathSegment::from(ident.clone()));
+        let key = path_key(&import_path);
+        self.serde_extended_structs.insert(key.clone(), import_path);
+        key
+    }
+
+    /// Check whether a top level module is the 'exports' module generated by wit-bindgen, given the
+    /// top level modules named `exports` that were already traversed
+    ///
+    /// Only a top level module can be the exports module (packages or interfaces named `exports` are not).
+    /// A WIT namespace that is literally named `exports` (which must be given explicitly) is generated as another
+    /// top level `exports` module, before the exports module, so only the second such module is the exports module
+    fn is_exports_module(&self, ident: &Ident) -> bool {
+        ident == EXPORTS_MODULE_NAME
+            && (self.seen_exports_named_module
+                || self
+                    .wit_ns_override
+                    .as_ref()
+                    .is_none_or(|ns| ns != EXPORTS_MODULE_NAME))
+    }
+
+    /// Check whether we are currently at a module *below* the 'exports' known module name
+    fn at_exported_module(&self) -> bool {
+        !self.parents.is_empty() && self.in_exports_module
+    }
+}
+
+/// Error message shown when no WIT package could be detected in the output of wit-bindgen
+const UNDETECTED_PACKAGE_ERROR_TEXT: &str = r#"failed to detect the WIT namespace and package in the output of wit-bindgen
+
+If the world is laid out such that detection fails, or picks the wrong namespace or package,
+specify them explicitly (with the braced form of args):
+
+    generate!(YourProvider, { world: "your-world", namespace: "your-ns", package: "your-package" });
+"#;
+
+/// Rust module name that is used by wit-bindgen to generate all the modules
+const EXPORTS_MODULE_NAME: &str = "exports";
+
+impl VisitMut for WitBindgenOutputVisitor {
+    fn visit_item_mod_mut(&mut self, node: &mut ItemMod) {
+        debug_print!("{}entering module [{}]", self.debug_location(), node.ident);
+
+        // Recognize the 'exports' module, which contains all the exported interfaces
+        if self.current_module_level() == 0 {
+            self.in_exports_module = self.is_exports_module(&node.ident);
+            self.seen_exports_named_module |= node.ident == EXPORTS_MODULE_NAME;
+        }
+
+        // Save the WIT namespace that we've recognized
+        //
+        // ASSUMPTION: The top level WIT namespace is always a module at @ level zero
+        // of the generated output, unless the namespace was given explicitly
+        if self.current_module_level() == 0 && !self.in_exports_module {
+            if self
+                .wit_ns_override
+                .as_ref()
+                .is_none_or(|ns| node.ident == ns)
+            {
+                self.wit_ns = Some(node.ident.to_string());
+            } else {
+                self.wit_ns = None;
+                self.wit_package = None;
+                self.wit_package_module = None;
+            }
+        }
+
+        // Save the WIT package name
+        //
+        // ASSUMPTION: The level 1 modules in the detected top level wasm namespace
+        // is the package the top level WIT package, unless the package was given explicitly
+        if self.current_module_level() == 1
+        // If we're one level in and the closest parent is the wasm namespace,
+        // we know this must be the package name
+            && self.at_wit_ns_module_child()
+            && !self.at_exported_module()
+        {
+            // Versions are only present in the module names generated by some versions of wit-bindgen,
+            // otherwise they are known from the resolved packages
+            let (package, version) = split_package_version(&node.ident.to_string());
+            let version = version.or_else(|| {
+                self.package_versions
+                    .get(&(self.wit_ns.clone().unwrap_or_default(), package.clone()))
+                    .cloned()
+            });
+            if self
+                .wit_package_override
+                .as_ref()
+                .is_some_and(|p| *p != package)
+            {
+                // Other packages are still traversed (for their types), but their functions are not gathered
+                self.wit_package = None;
+                self.wit_package_module = None;
+            } else {
+                self.wit_packages
+                    .entry(self.wit_ns.clone().unwrap_or_default())
+                    .or_default()
+                    .insert(package.clone());
+                self.wit_package = Some(package);
+                self.wit_package_version = version;
+                self.wit_package_module = Some(node.ident.to_string());
+            }
+        }
+
+        // ASSUMPTION: modules at level 2 or deeper contain externally visible *or* used interfaces
+        // (i.e. ones that are exported)
+        // 'use' calls will  cause an interface to show up, but only if the
+        // thing that uses it is imported/exported
+        //
+        // NOTE: worlds pulled in with `include` are merged into the world by wit-bindgen before generation,
+        // so their interfaces are generated at the same (<ns> -> <package> -> <interface>) paths as the world's
+        // own, and anything below 'exports' (at any depth) is recognized by the root module alone
+
+        // Recur/Traverse deeper into the detected modules where possible
+        if let Some((_, ref mut items)) = &mut node.content {
+            // Save the current module before we go spelunking
+            self.parents.push(node.ident.clone());
+
+            for item in items {
+                self.visit_item_mut(item);
+            }
+
+            self.parents.pop();
+        }
+    }
+
+    fn visit_item_mut(&mut self, node: &mut syn::Item) {
+        match node {
+            Item::Fn(f) => {
+                debug_print!("{}visiting fn [{}]", self.debug_location(), f.sig.ident);
+
+                // If we're visiting a function that is inside a non-export, at any depth below
+                // the top level package, we must gather the function calls to make lattice messages out of
                 // the arguments so they can be received via the lattice
-                match (&self.wit_package, &self.current_module_name()) {
-                    (Some(pkg), Some(module_name))
-                        if !self.at_exported_module() && self.at_grandchild_of_module(pkg) =>
+                if let Some(iface_path) = self.current_import_interface() {
+                    // Find functions in traits that we must stub later
+                    self.import_trait_fns
+                        .entry(iface_path.clone())
+                        .or_default()
+                        .push(f.clone());
+
+                    // Save the path to the module so the functions can be called directly
+                    self.import_module_paths
+                        .entry(iface_path)
+                        .or_insert_with(|| {
+                            self.parents
+                                .iter()
+                                .cloned()
+                                .map(PathSegment::from)
+                                .collect()
+                        });
+                }
+            }
+
+            Item::Struct(s) => {
+                debug_print!("{}visiting struct [{}]", self.debug_location(), s.ident);
+
+                // For all structs that we encounter defined natively in this package,
+                // we want to inject serde's Serialize & Deserialize
+                append_serde_derives(&mut s.attrs, self.serde_path.as_ref());
+
+                // Mark any fields that were requested to be flattened, so that
+                // embedded records can be received as flat payloads
+                let mut flattened = false;
+                for field in s.fields.iter_mut() {
+                    let Some(field_name) = &field.ident else {
+                        continue;
+                    };
+                    if self
+                        .flatten_fields
+                        .contains(&(s.ident.to_string(), field_name.to_string()))
                     {
-                        // Find functions in traits that we must stub later
-                        self.import_trait_fns
-                            .entry(module_name.clone())
-                            .or_default()
-                            .push(f.clone());
+                        field.attrs.push(syn::parse_quote!(#[serde(flatten)]));
+                        flattened = true;
+                        debug_print!(
+                            "{}marked field [{}] of struct [{}] as flattened",
+                            self.debug_location(),
+                            field_name,
+                            s.ident
+                        );
                     }
-                    _ => {}
+                }
+
+                // Fields may be (de)serialized with their original WIT (kebab-case) names
+                // (ex. `content-type` rather than `content_type`), for peers on the lattice
+                // that are not written in Rust
+                if self.wit_field_names {
+                    for field in s.fields.iter_mut() {
+                        if let Some(field_name) = &field.ident {
+                            field.attrs.extend(wit_field_name_attr(field_name));
+                        }
+                    }
+                }
+
+                // Optional fields are defaulted when missing, so records sent by peers built against older
+                // versions of the WIT can still be received (as for invocation structs)
+                for field in s.fields.iter_mut() {
+                    if is_option_type(&field.ty) {
+                        field.attrs.push(syn::parse_quote!(#[serde(default)]));
+                    }
+                }
+
+                // NOTE: serde does not support denying unknown fields alongside flattened ones
+                if self.deny_unknown_fields && !flattened {
+                    s.attrs
+                        .push(syn::parse_quote!(#[serde(deny_unknown_fields)]));
+                }
+
+                let key = self.save_serde_extended_path(&s.ident);
+
+                // With borrowing ownership, wit-bindgen generates a borrowed form of records whose fields borrow
+                // (ex. `BrokerMessageParam<'a>`) alongside an owned form (ex. `BrokerMessageResult`), of which
+                // only the owned one can be received over the lattice
+                let name = s.ident.to_string();
+                if let Some(stem) = name
+                    .strip_suffix("Param")
+                    .filter(|_| s.generics.lifetimes().next().is_some())
+                {
+                    debug_print!(
+                        "{}detected borrowed form [{}] of record [{}]",
+                        self.debug_location(),
+                        name,
+                        stem
+                    );
+                    self.owned_struct_forms.insert(key, format!("{stem}Result"));
                 }
             }
 
-            Item::Struct(s) => {
-                debug_print(format!(
-                    "{}> [(lvl {}) module:{:?}] visiting struct {:?}",
-                    "=".repeat(self.current_module_level()),
-                    self.current_module_level(),
-                    self.parents.last(),
-                    s.ident,
-                ));
+            // WIT flags are generated with `bitflags!`, whose structs can't be extended with serde's derives,
+            // so they are recorded for (de)serialization as their bits to be implemented separately
+            Item::Macro(m)
+                if m.mac
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|s| s.ident == "bitflags") =>
+            {
+                let Some((name, repr)) = parse_bitflags_struct(m.mac.tokens.clone()) else {
+                    return;
+                };
+                debug_print!(
+                    "{}detected flags [{name}] with bits of type [{repr}]",
+                    self.debug_location()
+                );
+                let key = self.save_serde_extended_path(&name);
+                self.flags_types.insert(key, repr);
+            }
 
-                // For all structs that we encounter defined natively in this package,
-                // we want to inject serde's Serialize & Deserialize
-                for attr in &mut s.attrs {
-                    if let Attribute {
-                        style: AttrStyle::Outer,
-                        meta:
-                            Meta::List(MetaList {
-                                path,
-                                ref mut tokens,
-                                ..
-                            }),
-                        ..
-                    } = attr
-                    {
-                        if path.get_ident().is_some_and(|v| v.to_string() == "derive") {
-                            let mut serialize_macro = Punctuated::<Path, Token![::]>::new();
-                            serialize_macro
-                                .push(Path::from(Ident::new("serde", Span::call_site())));
-                            serialize_macro
-                                .push(Path::from(Ident::new("Serialize", Span::call_site())));
-
-                            let mut deserialize_macro = Punctuated::<Path, Token![::]>::new();
-                            deserialize_macro
-                                .push(Path::from(Ident::new("serde", Span::call_site())));
-                            deserialize_macro
-                                .push(Path::from(Ident::new("Deserialize", Span::call_site())));
-
-                            // Add Serialize/Serialize onto the derive
-                            tokens.append_all(&[
-                                Punct::new(',', Spacing::Alone).to_token_stream(),
-                                serialize_macro.to_token_stream(),
-                                Punct::new(',', Spacing::Alone).to_token_stream(),
-                                deserialize_macro.to_token_stream(),
-                            ]);
+            // WIT enums and variants are generated as Rust enums, which need serde's derives
+            // just like records do, since they can also be arguments and results
+            Item::Enum(e) => {
+                debug_print!("{}visiting enum [{}]", self.debug_location(), e.ident);
 
-                            debug_print(format!(
-                                "detected & appended serialize/deserialize to derive for: {:?}",
-                                attr.path().get_ident()
-                            ));
-                        }
-                    }
+                append_serde_derives(&mut e.attrs, self.serde_path.as_ref());
+                self.save_serde_extended_path(&e.ident);
+            }
+
+            Item::Mod(m) => self.visit_item_mod_mut(m),
+
+            // Nothing we gather lives inside other items (ex. the bodies of the functions and trait impls
+            // that make up most of wit-bindgen's output), so they are not traversed, which keeps
+            // visiting fast for large worlds
+            _ => {}
+        }
+    }
+}
+
+/// Find the name and integer type of the struct declared in a `bitflags!` invocation
+/// (ex. `Permissions` and `u8` for `pub struct Permissions: u8 { ... }`)
+fn parse_bitflags_struct(tokens: proc_macro2::TokenStream) -> Option<(Ident, Ident)> {
+    let tokens = tokens.into_iter().collect::<Vec<TokenTree>>();
+    tokens.windows(4).find_map(|w| match w {
+        [TokenTree::Ident(kw), TokenTree::Ident(name), TokenTree::Punct(p), TokenTree::Ident(repr)]
+            if kw == "struct" && p.as_char() == ':' =>
+        {
+            Some((name.clone(), repr.clone()))
+        }
+        _ => None,
+    })
+}
+
+/// Build Serialize & Deserialize impls for structs generated for WIT flags,
+/// which are (de)serialized as their underlying bits
+fn build_flags_serde_impls(
+    struct_lookup: &HashMap<String, Punctuated<PathSegment, PathSep>>,
+    flags_types: &HashMap<String, Ident>,
+    serde_path: &Path,
+) -> proc_macro2::TokenStream {
+    let allow_lints = allow_lints_attr();
+    let mut tokens = proc_macro2::TokenStream::new();
+    for (key, repr) in flags_types.iter() {
+        let Some(path) = struct_lookup.get(key) else {
+            continue;
+        };
+        tokens.append_all(quote::quote!(
+            #allow_lints
+            impl #serde_path::Serialize for #path {
+                fn serialize<S: #serde_path::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    #serde_path::Serialize::serialize(&self.bits(), serializer)
                 }
+            }
 
-                // Save import paths for structs that are extended
-                let mut struct_import_path = Punctuated::<syn::PathSegment, Token![::]>::new();
-                for p in self.parents.iter() {
-                    struct_import_path.push(syn::PathSegment::from(p.clone()));
+            #allow_lints
+            impl<'de> #serde_path::Deserialize<'de> for #path {
+                fn deserialize<D: #serde_path::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    <#repr as #serde_path::Deserialize>::deserialize(deserializer).map(Self::from_bits_retain)
                 }
-                struct_import_path.push(syn::PathSegment::from(s.ident.clone()));
-                self.serde_extended_structs
-                    .insert(s.ident.to_string(), struct_import_path);
-                // TODO: it is possible to have two similarly named structs but from different packages/interfaces
             }
+        ));
+    }
+    tokens
+}
+
+/// Append serde's Serialize & Deserialize to the derive attribute of a generated type,
+/// pointing the derives at the given serde crate if it is renamed or re-exported
+fn append_serde_derives(attrs: &mut Vec<Attribute>, serde_path: Option<&Path>) {
+    let serde: Path = serde_path
+        .cloned()
+        .unwrap_or_else(|| syn::parse_quote!(::serde));
+    for attr in attrs.iter_mut() {
+        if let Attribute {
+            style: AttrStyle::Outer,
+            meta:
+                Meta::List(MetaList {
+                    path,
+                    ref mut tokens,
+                    ..
+                }),
+            ..
+        } = attr
+        {
+            if path.get_ident().is_some_and(|v| v == "derive") {
+                let serialize_macro: Path = syn::parse_quote!(#serde::Serialize);
+                let deserialize_macro: Path = syn::parse_quote!(#serde::Deserialize);
+
+                // Add Serialize/Serialize onto the derive
+                tokens.append_all(&[
+                    Punct::new(',', Spacing::Alone).to_token_stream(),
+                    serialize_macro.to_token_stream(),
+                    Punct::new(',', Spacing::Alone).to_token_stream(),
+                    deserialize_macro.to_token_stream(),
+                ]);
 
-            _ => visit_item_mut(self, node),
+                debug_print!(
+                    "appended serialize/deserialize to derive [{}]",
+                    attr.path().to_token_stream()
+                );
+            }
         }
     }
+
+    if let Some(serde_path) = serde_path {
+        let serde_path = pretty_tokens(serde_path.to_token_stream());
+        attrs.push(syn::parse_quote!(#[serde(crate = #serde_path)]));
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -527,38 +3165,148 @@ struct LatticeMethod {
     /// Tokens that represent the struct member declarations
     struct_members: proc_macro2::TokenStream,
     /// Function name for the method that will be called after a lattice invocation is received
+    ///
+    /// Functions named after Rust keywords are raw identifiers (ex. `r#type`), as generated by wit-bindgen,
+    /// so names derived from this one (ex. of the lattice method) must be built from its unraw'd form
     func_name: Ident,
     /// Invocation arguments (i.e. invocation struct members)
     invocation_args: Vec<Ident>,
+    /// Types of the invocation arguments (i.e. of the invocation struct members), in the same order
+    invocation_arg_types: Vec<proc_macro2::TokenStream>,
     /// Invocation arguments (i.e. invocation struct members)
     invocation_return: ReturnType,
+    /// Whether the method returns a `Result` (ex. `-> Result<u64, String>`), rather than being
+    /// infallible (ex. `-> u64`, `-> Option<String>` or no return at all)
+    fallible: bool,
+    /// Whether every struct member implements `Default` (so the struct can derive it)
+    members_default: bool,
+    /// Whether the method returns a `Result` with an error type defined by the WIT,
+    /// which is serialized as part of the whole result rather than stringified
+    structured_error: bool,
+    /// Type of the only argument of the method, when it is received directly rather than in a struct
+    single_arg_type: Option<proc_macro2::TokenStream>,
+    /// Whether the method receives the invocation body and responds with raw bytes, without serialization
+    raw: bool,
+    /// Doc attributes of the function generated by wit-bindgen (i.e. the WIT function's doc comments)
+    docs: Vec<Attribute>,
 }
 
 /// Build <X>ArgumentObjects from functions that were detected as imports
 fn build_lattice_methods_by_wit_interface(
-    wit_pkg_name: &WitPackageName,
     struct_lookup: &HashMap<String, Punctuated<PathSegment, PathSep>>,
-    map: &HashMap<WitInterfaceName, Vec<syn::ItemFn>>,
-) -> HashMap<WitInterfaceName, Vec<LatticeMethod>> {
+    owned_forms: &HashMap<String, String>,
+    map: &HashMap<WitInterfacePath, Vec<syn::ItemFn>>,
+    module_paths: &HashMap<WitInterfacePath, Punctuated<PathSegment, PathSep>>,
+    provider_opts: &ProviderOpts,
+    owning_bindings: bool,
+) -> syn::Result<HashMap<WitInterfaceName, Vec<LatticeMethod>>> {
+    let mut conversion_errors: Vec<syn::Error> = Vec::new();
     let mut methods_by_name: HashMap<WitInterfaceName, Vec<LatticeMethod>> = HashMap::new();
+    let string_type = &provider_opts
+        .string_type
+        .clone()
+        .unwrap_or_else(|| syn::parse_quote!(String));
+    let iface_names = unique_interface_names(map.keys());
+
+    // Shorter invocation struct names are only used when they are unique, among the invocation structs
+    // as well as the types generated by wit-bindgen
+    let mut short_struct_name_counts: HashMap<String, usize> = HashMap::new();
+    for (iface_path, funcs) in map.iter() {
+        for f in funcs.iter() {
+            if let Some(name) = short_invocation_struct_name(
+                provider_opts.struct_naming,
+                &iface_path.iface,
+                &f.sig.ident,
+            ) {
+                *short_struct_name_counts.entry(name).or_default() += 1;
+            }
+        }
+    }
 
     // Per module import we must build up a different structs
-    for (wit_iface_name, funcs) in map.iter() {
+    for (iface_path, funcs) in map.iter() {
+        let WitInterfacePath {
+            ns: wit_ns,
+            package: wit_pkg_name,
+            version: wit_pkg_version,
+            iface: wit_iface_name,
+        } = iface_path;
+        let type_lookup = TypeLookup {
+            paths: struct_lookup,
+            scope: module_paths.get(iface_path),
+            owned_forms,
+        };
+        let struct_pkg_name = match wit_pkg_version {
+            Some(v) if provider_opts.versioned_struct_names => {
+                format!("{wit_pkg_name}_v{v}").to_upper_camel_case()
+            }
+            _ => wit_pkg_name.to_upper_camel_case(),
+        };
+
+        // WIT functions whose names map to the same Rust ident would produce duplicate trait methods
+        // (and dispatch arms), so report them by their WIT names rather than let rustc report the duplicates
+        let mut seen_func_names: HashMap<String, &syn::ItemFn> = HashMap::new();
+        for f in funcs.iter() {
+            let func_name = f.sig.ident.unraw().to_string();
+            if let Some(existing) = seen_func_names.insert(func_name.clone(), f) {
+                conversion_errors.push(syn::Error::new(
+                    f.sig.span(),
+                    format!(
+                        "WIT functions `{}` and `{}` of interface `{wit_iface_name}` both map to method `{func_name}`",
+                        wit_func_name(existing),
+                        wit_func_name(f),
+                    ),
+                ));
+            }
+        }
+
         for f in funcs.iter() {
             // Create an identifier for the new struct that will represent the function invocation coming
             // across the lattice, in a <CamelCaseModule><CamelCaseInterface><CamelCaseFunctionName> pattern
             // (ex. MessagingConsumerRequestMultiInvocation)
             let lattice_method_name = LitStr::new(
-                format!("Message.{}", f.sig.ident.to_string().to_upper_camel_case()).as_ref(),
+                &match provider_opts.lattice_method_style {
+                    // ex. Message.RequestMulti
+                    LatticeMethodStyle::Legacy => {
+                        format!(
+                            "Message.{}",
+                            f.sig.ident.unraw().to_string().to_upper_camel_case()
+                        )
+                    }
+                    // ex. wasmcloud:messaging/consumer.request-multi
+                    // (ex. wasmcloud:keyvalue/key-value@0.2.0.get for versioned packages)
+                    LatticeMethodStyle::Wit => format!(
+                        "{}:{}/{}{}.{}",
+                        wit_ns.to_kebab_case(),
+                        wit_pkg_name.to_kebab_case(),
+                        wit_iface_name.to_kebab_case(),
+                        wit_pkg_version
+                            .as_ref()
+                            .map(|v| format!("@{}", v.replace('_', ".")))
+                            .unwrap_or_default(),
+                        f.sig.ident.unraw().to_string().to_kebab_case()
+                    ),
+                },
                 Span::call_site(),
             );
 
-            let struct_name = format_ident!(
-                "{}{}{}Invocation",
-                wit_pkg_name.to_upper_camel_case(),
-                wit_iface_name.to_upper_camel_case(),
-                f.sig.ident.to_string().to_upper_camel_case()
-            );
+            let struct_name = match short_invocation_struct_name(
+                provider_opts.struct_naming,
+                wit_iface_name,
+                &f.sig.ident,
+            ) {
+                Some(name)
+                    if short_struct_name_counts[&name] == 1 && type_lookup.get(&name).is_none() =>
+                {
+                    format_ident!("{name}")
+                }
+                _ => format_ident!(
+                    "{}{}{}Invocation",
+                    struct_pkg_name,
+                    wit_iface_name.to_upper_camel_case(),
+                    f.sig.ident.unraw().to_string().to_upper_camel_case()
+                ),
+            };
 
             // wit-bindgen generates functions that borrow (regardless of what opts.ownership is set to),
             // fucntions that look like the following could be generated:
@@ -566,11 +3314,20 @@ fn build_lattice_methods_by_wit_interface(
             // - fn request(subject : & str, body : Option < & [u8] >, timeout_ms : u32,) -> Result < BrokerMessage, wit_bindgen :: rt :: string :: String >
             // - fn request_multi(subject : & str, body : Option < & [u8] >, timeout_ms : u32, max_results : u32,) -> Result < wit_bindgen :: rt :: vec :: Vec :: < BrokerMessage >, wit_bindgen :: rt :: string :: String >
             // - fn publish(msg : & BrokerMessage,) -> Result < (), wit_bindgen :: rt :: string :: String >
+            // - fn delete_many(keys : Vec < & str >,) -> Result < u32, wit_bindgen :: rt :: string :: String >
+            // - fn publish_batch(msgs : & [BrokerMessage],) -> Result < (), wit_bindgen :: rt :: string :: String >
             //
             // Since these arguments use lifetimes, we can't just convert them to structs without either naming or *removing* the lifetimes (via converting to owned data)
+            //
+            // With `ownership: Owning`, records are generated without lifetimes, so only arguments that still
+            // borrow (ex. strings and lists) are converted, while the others are only qualified
 
             // Build a list of invocation arguments similar to the structs
             let mut invocation_args: Vec<Ident> = Vec::new();
+            let mut invocation_arg_types: Vec<proc_macro2::TokenStream> = Vec::new();
+
+            // Track whether all the members can be defaulted
+            let mut members_default = true;
 
             // Transform the members and remove any lifetimes by manually converting references to owned data
             // (i.e. doing things like converting a type like &str to String mechanically)
@@ -588,129 +3345,68 @@ fn build_lattice_methods_by_wit_interface(
 
                     // Match on a single input argument in the function signature
                     match &arg
-                            .to_token_stream()
-                            .into_iter()
-                            .collect::<Vec<TokenTree>>()[..]
-                        {
-                            // pattern: 'name: &T'
-                            simple_ref @ &[
-                                TokenTree::Ident(ref n), // name
-                                TokenTree::Punct(_), // :
-                                TokenTree::Punct(ref p), // &
-                                TokenTree::Ident(ref t), // T
-                            ] if p.as_char() == '&' => {
-                                // Save the invocation argument for later
-                                invocation_args.push(n.clone());
-
-                                // Match the type that came out of the simple case
-                                match t.to_string().as_str() {
-                                    // A &str
-                                    "str" => {
-                                        tokens.append_all([
-                                            &simple_ref[0],
-                                            &simple_ref[1],
-                                            // replace the type with an owned string
-                                            &TokenTree::Ident(Ident::new("String", t.span())),
-                                        ]);
-                                    },
-
-                                    // Unexpected non-standard type as reference
-                                    // (likely a known custom type generated by wit-bindgen)
-                                    _ => {
-
-                                        // Add a modified group of tokens to the list for the struct
-                                        tokens.append_all([
-                                            &simple_ref[0], // name
-                                            &simple_ref[1], // colon
-                                        ]);
-
-                                        // If we have a T that this module defined, we must use the full path to it
-                                        // if not, it is likely a builtin, so we can use it directly
-                                        if let Some(v) = struct_lookup.get(&simple_ref[3].to_string()) {
-                                            tokens.append_all([ v.to_token_stream() ]);
-                                        } else {
-                                            tokens.append_all([ &simple_ref[3]]);
-                                        };
-                                    }
-                                }
-                            },
-
-                            // pattern: 'name: Wrapper<&T>'
-                            wrapped_ref @ &[
-                                TokenTree::Ident(ref n),  // name
-                                TokenTree::Punct(_),  // :
-                                TokenTree::Ident(_),  // Wrapper
-                                TokenTree::Punct(ref p),  // <
-                                TokenTree::Punct(ref p2), // &
-                                ..,  // T
-                                TokenTree::Punct(_) // >
-                            ] if p.as_char() == '<' && p2.as_char() == '&' => {
-                                // Save the invocation argument for later
-                                invocation_args.push(n.clone());
-
-                                // Slice out the parts in between the < ... >
-                                let type_section = &wrapped_ref[4..wrapped_ref.len()];
-
-                                match &type_section[..] {
-                                    // case: str
-                                    [
-                                        TokenTree::Punct(_), // <
-                                        TokenTree::Ident(ref n),
-                                        TokenTree::Punct(_) // >
-                                    ] if n.to_string().as_str() == "str" => {
-                                        tokens.append_all([
-                                            &wrapped_ref[0], // name
-                                            &wrapped_ref[1], // colon
-                                            &wrapped_ref[2], // wrapper
-                                            &wrapped_ref[3], // <
-                                            &TokenTree::Ident(Ident::new("String", n.span())),
-                                            &wrapped_ref[5], // >
-                                        ]);
-                                    },
-
-                                    // case: [u8]
-                                    [
-                                        TokenTree::Punct(_), // <
-                                        TokenTree::Group(g),
-                                        TokenTree::Punct(_), // >
-                                    ] if g.to_string().as_str() == "[u8]" => {
-                                        tokens.append_all([
-                                            &wrapped_ref[0], // name
-                                            &wrapped_ref[1], // colon
-                                            &wrapped_ref[2], // wrapper
-                                            &wrapped_ref[3], // <
-                                            &TokenTree::Ident(Ident::new("Vec", Span::call_site())), // Vec
-                                            &TokenTree::Punct(Punct::new('<', Spacing::Joint)), // <
-                                            &TokenTree::Ident(Ident::new("u8", Span::call_site())), // u8
-                                            &TokenTree::Punct(Punct::new('>', Spacing::Joint)), // >
-                                            &TokenTree::Punct(Punct::new('>', Spacing::Joint)), // >
-                                        ]);
-                                    },
-
-                                    rest =>  {
-                                        // If we have a < T >, and T is a struct this module defined, we must use the full path to it
-                                        // if not, it is likely a builtin, so we can use it directly
-                                        if let Some(v) = struct_lookup.get(&rest[1].to_string()) {
-                                            tokens.append_all(&wrapped_ref[0..5]);
-                                            tokens.append_all([ v.to_token_stream() ]);
-                                            tokens.append_all(&wrapped_ref[6..]);
-                                        } else {
-                                            tokens.append_all(wrapped_ref);
-                                        };
-                                    },
-                                }
-                            },
+                        .to_token_stream()
+                        .into_iter()
+                        .collect::<Vec<TokenTree>>()[..]
+                    {
+                        // pattern: 'name: T', where T may contain references at any depth
+                        // (ex. '&str', 'Option<&[u8]>', 'Option<Vec<&str>>', '(u32, &str)')
+                        //
+                        // Bare slices (ex. 'data: &[u8]' for a WIT 'write(data: list<u8>)') become owned lists:
+                        // - '&[u8]' -> 'Vec<u8>'
+                        //
+                        // Lists of references are common for batch operations, and are converted element-wise:
+                        // - 'Vec<&str>' -> 'Vec<String>'
+                        // - 'Vec<&BrokerMessage>' -> 'Vec<path::to::BrokerMessage>'
+                        // - '&[&str]' -> 'Vec<String>'
+                        typed @ &[
+                            TokenTree::Ident(ref n), // name
+                            TokenTree::Punct(ref colon), // :
+                            ..
+                        ] if colon.as_char() == ':' => {
+                            // Save the invocation argument for later
+                            invocation_args.push(n.clone());
 
-                            // pattern: unknown
-                            ts => {
-                                // Save the first token (which should be the argument name) as an invocation argument for later
-                                if let TokenTree::Ident(name) = &ts[0] {
-                                    invocation_args.push(name.clone());
-                                }
+                            tokens.append_all([
+                                &typed[0], // name
+                                &typed[1], // colon
+                            ]);
+                            let arg_ty: proc_macro2::TokenStream = typed[2..].iter().cloned().collect();
+                            let mut ty = if owning_bindings && !contains_reference(arg_ty.clone()) {
+                                qualify_type(arg_ty, type_lookup)
+                            } else {
+                                owned_type(&typed[2..], type_lookup, string_type)
+                            };
+                            if provider_opts.map_lists {
+                                ty = map_list_type(&ty, string_type).unwrap_or(ty);
+                            }
+                            members_default &= is_default_type(ty.clone());
+
+                            // References that could not be converted would otherwise surface as
+                            // lifetime errors deep inside the generated code
+                            if contains_reference(ty.clone()) {
+                                conversion_errors.push(unsupported_arg_error(arg, &f.sig.ident));
+                            }
+
+                            invocation_arg_types.push(ty.clone());
+                            tokens.append_all([ty]);
+                        }
 
-                                tokens.append_all(ts);
+                        // pattern: unknown
+                        ts => {
+                            // Save the first token (which should be the argument name) as an invocation argument for later
+                            if let TokenTree::Ident(name) = &ts[0] {
+                                invocation_args.push(name.clone());
+                                invocation_arg_types.push(ts.iter().skip(2).cloned().collect());
+                            }
+                            members_default = false;
+                            if contains_reference(ts.iter().cloned().collect()) {
+                                conversion_errors.push(unsupported_arg_error(arg, &f.sig.ident));
                             }
+
+                            tokens.append_all(ts);
                         }
+                    }
 
                     tokens
                 });
@@ -718,8 +3414,47 @@ fn build_lattice_methods_by_wit_interface(
             // Add the struct and it's members to a list that will be used in another quote
             // it cannot be added directly/composed to a TokenStream here to avoid import conflicts
             // in case bindgen-defined types are used.
+            // The type of a single argument follows its name and colon (ex. `key: String`)
+            let single_arg_type = (provider_opts.unwrap_single_args && invocation_args.len() == 1)
+                .then(|| struct_members.clone().into_iter().skip(2).collect());
+
+            // Only errors of types that were extended with serde derives can be serialized
+            let err_type = result_err_type(&f.sig.output);
+            let structured_error = provider_opts.structured_errors
+                && err_type.is_some_and(|e| match e {
+                    syn::Type::Path(p) => p
+                        .path
+                        .segments
+                        .last()
+                        .is_some_and(|s| type_lookup.get(&s.ident.to_string()).is_some()),
+                    _ => false,
+                });
+
+            // Methods receiving and returning raw bytes must take a single byte list and return one
+            let raw = provider_opts
+                .raw_methods
+                .contains(&f.sig.ident.unraw().to_string());
+            if raw {
+                let is_bytes =
+                    |ts: proc_macro2::TokenStream| ts.to_string().replace(' ', "") == "Vec<u8>";
+                let arg_is_bytes = invocation_args.len() == 1
+                    && is_bytes(struct_members.clone().into_iter().skip(2).collect());
+                let return_is_bytes =
+                    is_bytes(result_ok_type(&f.sig.output, type_lookup).to_token_stream());
+                if !arg_is_bytes || !return_is_bytes {
+                    conversion_errors.push(syn::Error::new(
+                        f.sig.span(),
+                        format!(
+                            "raw method `{}` must take a single `list<u8>` argument and return a `list<u8>` \
+                             (optionally in a `result`), as its invocations are sent without serialization",
+                            f.sig.ident.unraw().to_string().to_kebab_case(),
+                        ),
+                    ));
+                }
+            }
+
             methods_by_name
-                .entry(wit_iface_name.to_string().to_upper_camel_case())
+                .entry(iface_names[iface_path].clone())
                 .or_default()
                 .push(LatticeMethod {
                     lattice_method_name,
@@ -727,18 +3462,883 @@ fn build_lattice_methods_by_wit_interface(
                     struct_members,
                     func_name: f.sig.ident.clone(),
                     invocation_args,
+                    invocation_arg_types,
                     invocation_return: f.sig.output.clone(),
+                    fallible: err_type.is_some(),
+                    members_default,
+                    structured_error: structured_error && !raw,
+                    single_arg_type,
+                    raw,
+                    docs: f
+                        .attrs
+                        .iter()
+                        .filter(|a| a.path().is_ident("doc"))
+                        .cloned()
+                        .collect(),
+                });
+        }
+    }
+
+    // Report every argument that could not be converted at once
+    let mut conversion_errors = conversion_errors.into_iter();
+    if let Some(mut e) = conversion_errors.next() {
+        e.extend(conversion_errors);
+        return Err(e);
+    }
+
+    Ok(methods_by_name)
+}
+
+/// Build the shorter name of an invocation struct for the given naming scheme (ex. `GetInvocation`),
+/// if the scheme uses one rather than the full name
+fn short_invocation_struct_name(
+    naming: StructNaming,
+    wit_iface_name: &str,
+    func_name: &Ident,
+) -> Option<String> {
+    let func_name = func_name.unraw().to_string().to_upper_camel_case();
+    match naming {
+        StructNaming::Full => None,
+        StructNaming::Interface => Some(format!(
+            "{}{func_name}Invocation",
+            wit_iface_name.to_upper_camel_case()
+        )),
+        StructNaming::Short => Some(format!("{func_name}Invocation")),
+    }
+}
+
+/// Check whether wit-bindgen args (ex. `{ world: "...", ownership: Owning }`) set the `Owning` ownership mode
+fn is_owning_ownership(bindgen_args: &proc_macro2::TokenStream) -> bool {
+    let Some(TokenTree::Group(g)) = bindgen_args.clone().into_iter().next() else {
+        return false;
+    };
+    g.delimiter() == Delimiter::Brace
+        && g.stream()
+            .into_iter()
+            .collect::<Vec<TokenTree>>()
+            .windows(3)
+            .any(|w| {
+                matches!(
+                    w,
+                    [TokenTree::Ident(o), TokenTree::Punct(c), TokenTree::Ident(v)]
+                        if o == "ownership" && c.as_char() == ':' && v == "Owning"
+                )
+            })
+}
+
+/// Get the name of the WIT function that wit-bindgen generated a function for, from the `link_name` of the import
+/// in its body (ex. `get-A` for `get_a`), falling back to the kebab-case name of the function
+fn wit_func_name(f: &syn::ItemFn) -> String {
+    fn find_link_name(ts: proc_macro2::TokenStream) -> Option<String> {
+        let tokens = ts.into_iter().collect::<Vec<TokenTree>>();
+        tokens
+            .windows(3)
+            .find_map(|w| match w {
+                [TokenTree::Ident(i), TokenTree::Punct(eq), TokenTree::Literal(l)]
+                    if i == "link_name" && eq.as_char() == '=' =>
+                {
+                    syn::parse2::<LitStr>(l.to_token_stream())
+                        .ok()
+                        .map(|s| s.value())
+                }
+                _ => None,
+            })
+            .or_else(|| {
+                tokens.iter().find_map(|tt| match tt {
+                    TokenTree::Group(g) => find_link_name(g.stream()),
+                    _ => None,
+                })
+            })
+    }
+    find_link_name(f.block.to_token_stream())
+        .unwrap_or_else(|| f.sig.ident.unraw().to_string().to_kebab_case())
+}
+
+/// Check whether a type contains a reference or a lifetime (at any depth), ex. `&str` or `Metadata<'_>`
+/// (a borrowed record without an owned form)
+fn contains_reference(ty: proc_macro2::TokenStream) -> bool {
+    ty.into_iter().any(|tt| match tt {
+        TokenTree::Punct(p) => p.as_char() == '&' || p.as_char() == '\'',
+        TokenTree::Group(g) => contains_reference(g.stream()),
+        _ => false,
+    })
+}
+
+/// Build the attribute that (de)serializes a field with its original WIT (kebab-case) name
+/// (ex. `#[serde(rename = "content-type")]` for `content_type`), if it differs from the Rust one
+fn wit_field_name_attr(field_name: &Ident) -> Option<Attribute> {
+    let rust_name = field_name.unraw().to_string();
+    let wit_name = rust_name.to_kebab_case();
+    (wit_name != rust_name).then(|| syn::parse_quote!(#[serde(rename = #wit_name)]))
+}
+
+/// Check whether a type is an `Option` (ex. `Option<String>` or `core::option::Option<u32>`)
+fn is_option_type(ty: &syn::Type) -> bool {
+    matches!(
+        ty,
+        syn::Type::Path(p) if p.qself.is_none() && p.path.segments.last().is_some_and(|s| s.ident == "Option")
+    )
+}
+
+/// Build the error for a function argument whose type could not be converted into an owned type
+fn unsupported_arg_error(arg: &syn::FnArg, func_name: &Ident) -> syn::Error {
+    syn::Error::new(
+        arg.span(),
+        format!(
+            "unsupported argument `{}` of WIT function `{}`: its type contains a reference that \
+             could not be converted to an owned type for receiving invocations over the lattice \
+             (ex. a record only passed to functions has no owned form unless bindings use `ownership: Owning`)",
+            arg.to_token_stream(),
+            func_name.unraw().to_string().to_kebab_case(),
+        ),
+    )
+}
+
+/// Remove the interfaces that are not served by the provider (per `only_interfaces` and `skip_interfaces`),
+/// erroring on names that match no interface with functions, which are likely typos
+fn select_served_interfaces(
+    lattice_fns: &mut HashMap<WitInterfacePath, Vec<syn::ItemFn>>,
+    provider_opts: &ProviderOpts,
+) -> syn::Result<()> {
+    let (only, skip) = (
+        &provider_opts.only_interfaces,
+        &provider_opts.skip_interfaces,
+    );
+    let found = lattice_fns
+        .keys()
+        .map(|p| p.iface.as_str())
+        .collect::<HashSet<&str>>();
+    let mut unknown = only
+        .iter()
+        .chain(skip.iter())
+        .filter(|i| !found.contains(i.as_str()))
+        .map(|i| i.to_kebab_case())
+        .collect::<Vec<String>>();
+    if !unknown.is_empty() {
+        unknown.sort();
+        let mut expected = found
+            .iter()
+            .map(|i| i.to_kebab_case())
+            .collect::<Vec<String>>();
+        expected.sort();
+        return Err(syn::Error::new(
+            Span::call_site(),
+            format!(
+                "unknown interfaces [{}] in only_interfaces/skip_interfaces; expected one of [{}]",
+                unknown.join(", "),
+                expected.join(", "),
+            ),
+        ));
+    }
+
+    lattice_fns
+        .retain(|p, _| (only.is_empty() || only.contains(&p.iface)) && !skip.contains(&p.iface));
+    Ok(())
+}
+
+/// Ensure that the methods acknowledged with `acknowledged_methods` are exactly the methods of the handled interfaces,
+/// so that a function added to the WIT fails the build until it is handled, rather than failing invocations at runtime
+fn check_acknowledged_methods(
+    acknowledged: &HashSet<String>,
+    methods_by_iface: &HashMap<WitInterfaceName, Vec<LatticeMethod>>,
+) -> syn::Result<()> {
+    let methods = methods_by_iface
+        .values()
+        .flatten()
+        .map(|m| m.func_name.unraw().to_string())
+        .collect::<HashSet<String>>();
+    let to_sorted_kebab = |names: Vec<&String>| {
+        let mut names = names
+            .into_iter()
+            .map(|n| format!("`{}`", n.to_kebab_case()))
+            .collect::<Vec<String>>();
+        names.sort();
+        names.join(", ")
+    };
+
+    let unacknowledged = methods.difference(acknowledged).collect::<Vec<&String>>();
+    if !unacknowledged.is_empty() {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            format!(
+                "methods {} are not acknowledged; implement them on the provider and add them to acknowledged_methods",
+                to_sorted_kebab(unacknowledged),
+            ),
+        ));
+    }
+    let unknown = acknowledged.difference(&methods).collect::<Vec<&String>>();
+    if !unknown.is_empty() {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            format!(
+                "acknowledged methods {} are not methods of the handled interfaces; remove them from acknowledged_methods",
+                to_sorted_kebab(unknown),
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Ensure that a provider declared in the same module as the generated code (i.e. referred to by a bare name)
+/// does not share the name of a generated item, and that none of its methods share the name of an associated item
+/// generated on it, either of which would produce conflicting definitions
+///
+/// Associated items that the provider defines itself (outside of `generate!`) are not visible to the macro, so
+/// a clash with those is left to the compiler, which reports it at the provider as the generated items are spanned to it
+fn check_provider_name_collisions(
+    impl_struct_name: &Path,
+    bindgen_items: &[Item],
+    methods_by_iface: &HashMap<WitInterfaceName, Vec<LatticeMethod>>,
+    provider_opts: &ProviderOpts,
+) -> syn::Result<()> {
+    // Associated consts and functions share a namespace, so the methods the provider implements for the WIT
+    // functions clash with the associated items generated on the provider
+    let mut associated = vec![("constant", "SUPPORTED_OPERATIONS")];
+    if provider_opts.entrypoint {
+        associated.push(("function", "start"));
+    }
+    if let Some((kind, name)) = associated.into_iter().find(|(_, name)| {
+        methods_by_iface
+            .values()
+            .flatten()
+            .any(|m| m.func_name.unraw() == name)
+    }) {
+        return Err(syn::Error::new(
+            impl_struct_name.span(),
+            format!(
+                "provider method `{name}` has the same name as the {kind} `{name}` generated on the provider"
+            ),
+        ));
+    }
+
+    let Some(provider) = impl_struct_name.get_ident() else {
+        return Ok(());
+    };
+    let provider_name = provider.unraw().to_string();
+
+    // Items generated by wit-bindgen (ex. the modules of WIT namespaces)
+    let mut generated = bindgen_items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Const(i) => Some(&i.ident),
+            Item::Enum(i) => Some(&i.ident),
+            Item::Fn(i) => Some(&i.sig.ident),
+            Item::Macro(i) => i.ident.as_ref(),
+            Item::Mod(i) => Some(&i.ident),
+            Item::Static(i) => Some(&i.ident),
+            Item::Struct(i) => Some(&i.ident),
+            Item::Trait(i) => Some(&i.ident),
+            Item::Type(i) => Some(&i.ident),
+            Item::Union(i) => Some(&i.ident),
+            _ => None,
+        })
+        .map(|ident| ("item", ident.unraw().to_string()))
+        .collect::<Vec<(&str, String)>>();
+
+    generated.push((
+        "module",
+        format!("{}_fallbacks", provider_name.to_snake_case()),
+    ));
+    generated.push(("module", "provider_method_checks".to_string()));
+    if !provider_opts.custom_dispatch {
+        generated.push(("type", "Operation".to_string()));
+    }
+    if provider_opts.dispatch_fn {
+        generated.push((
+            "function",
+            dispatch_fn_name(impl_struct_name, provider_opts).to_string(),
+        ));
+    }
+    if provider_opts.method_skeleton {
+        generated.push(("constant", "PROVIDER_METHOD_SKELETON".to_string()));
+    }
+    if provider_opts.contract_metadata {
+        generated.push(("constant", "PROVIDER_CONTRACT".to_string()));
+    }
+    if !provider_opts.method_concurrency.is_empty() {
+        generated.push(("module", "method_concurrency".to_string()));
+    }
+    if provider_opts.codec.is_some() {
+        generated.push(("trait", "InvocationCodec".to_string()));
+    } else if provider_opts.wire_format != WireFormat::Sdk {
+        generated.push(("module", "wire_format".to_string()));
+    }
+    if provider_opts.bench_harness {
+        generated.push(("module", "dispatch_bench".to_string()));
+    }
+    if provider_opts.generate_tests {
+        generated.push(("module", "invocation_round_trip_tests".to_string()));
+    }
+    for (wit_iface_name, methods) in methods_by_iface.iter() {
+        let wit_iface_trait = format!("{wit_iface_name}{}", provider_opts.trait_suffix);
+        if provider_opts.trait_style == TraitStyle::TraitVariant
+            && provider_opts.mode != MethodMode::Sync
+        {
+            generated.push(("trait", format!("Local{wit_iface_trait}")));
+        }
+        generated.push(("trait", wit_iface_trait));
+        if provider_opts.lattice_clients {
+            generated.push(("type", format!("{wit_iface_name}LatticeClient")));
+        }
+        if provider_opts.host_error.is_some() {
+            generated.push(("type", format!("{wit_iface_name}HostClient")));
+        }
+        generated.extend(
+            methods
+                .iter()
+                .map(|m| ("invocation struct", m.struct_name.to_string())),
+        );
+    }
+
+    match generated
+        .into_iter()
+        .find(|(_, name)| *name == provider_name)
+    {
+        Some((kind, name)) => Err(syn::Error::new(
+            provider.span(),
+            format!(
+                "provider `{provider_name}` has the same name as the generated {kind} `{name}`; \
+                 rename the provider (or declare it in another module and refer to it by path)"
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Ensure that no two interfaces contain methods with the same name, as the traits of all interfaces are
+/// implemented on the same provider struct
+fn check_method_name_collisions(
+    methods_by_iface: &HashMap<WitInterfaceName, Vec<LatticeMethod>>,
+) -> syn::Result<()> {
+    let mut ifaces_by_method: HashMap<String, Vec<&WitInterfaceName>> = HashMap::new();
+    for (wit_iface_name, methods) in methods_by_iface.iter() {
+        for m in methods.iter() {
+            ifaces_by_method
+                .entry(m.func_name.unraw().to_string())
+                .or_default()
+                .push(wit_iface_name);
+        }
+    }
+
+    let mut collisions = ifaces_by_method
+        .into_iter()
+        .filter(|(_, ifaces)| ifaces.len() > 1)
+        .map(|(method, mut ifaces)| {
+            ifaces.sort();
+            format!(
+                "`{method}` (in {})",
+                ifaces
+                    .iter()
+                    .map(|i| format!("`{i}`"))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )
+        })
+        .collect::<Vec<String>>();
+    if collisions.is_empty() {
+        return Ok(());
+    }
+    collisions.sort();
+
+    Err(syn::Error::new(
+        Span::call_site(),
+        format!(
+            "multiple WIT interfaces define methods with the same name, which would be ambiguous \
+             when implemented on the same provider: {}. Rename the functions in the WIT interfaces, \
+             or export the interfaces from separate providers",
+            collisions.join(", ")
+        ),
+    ))
+}
+
+/// Split the version suffix off of a package module name generated by wit-bindgen
+///
+/// Versioned packages (ex. `wasmcloud:keyvalue@0.2.0`) produce module names that carry the version
+/// (ex. `keyvalue_0_2_0` or `keyvalue_v0_2_0`), which is returned separately (ex. `0_2_0`)
+fn split_package_version(module_name: &str) -> (WitPackageName, Option<WitPackageVersion>) {
+    let segments = module_name.split('_').collect::<Vec<&str>>();
+    if segments.len() > 3 {
+        let (package, version) = segments.split_at(segments.len() - 3);
+        let major = version[0].strip_prefix('v').unwrap_or(version[0]);
+        let is_numeric = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+        if is_numeric(major) && is_numeric(version[1]) && is_numeric(version[2]) {
+            return (
+                package.join("_"),
+                Some(format!("{major}_{}_{}", version[1], version[2])),
+            );
+        }
+    }
+    (module_name.into(), None)
+}
+
+/// Determine the UpperCamelCase name used for each interface in generated code
+///
+/// Interfaces are named by their WIT interface name alone, unless interfaces with the same name
+/// exist in more than one package, in which case they are prefixed with their package name.
+fn unique_interface_names<'a>(
+    iface_paths: impl Iterator<Item = &'a WitInterfacePath> + Clone,
+) -> HashMap<WitInterfacePath, String> {
+    let mut packages_by_iface: HashMap<&WitInterfaceName, HashSet<(&str, &str)>> = HashMap::new();
+    for p in iface_paths.clone() {
+        packages_by_iface
+            .entry(&p.iface)
+            .or_default()
+            .insert((&p.ns, &p.package));
+    }
+    iface_paths
+        .map(|p| {
+            let name = if packages_by_iface[&p.iface].len() > 1 {
+                format!(
+                    "{}{}",
+                    p.package.to_upper_camel_case(),
+                    p.iface.to_upper_camel_case()
+                )
+            } else {
+                p.iface.to_upper_camel_case()
+            };
+            (p.clone(), name)
+        })
+        .collect()
+}
+
+/// Build a client per interface that calls the imported host functions, converting the errors
+/// of any functions returning a `Result` into the provider's error type (via `From`)
+fn build_host_clients(
+    struct_lookup: &HashMap<String, Punctuated<PathSegment, PathSep>>,
+    map: &HashMap<WitInterfacePath, Vec<syn::ItemFn>>,
+    module_paths: &HashMap<WitInterfacePath, Punctuated<PathSegment, PathSep>>,
+    host_error: &syn::Type,
+) -> proc_macro2::TokenStream {
+    let allow_lints = allow_lints_attr();
+    let mut tokens = proc_macro2::TokenStream::new();
+    let iface_names = unique_interface_names(map.keys());
+    for (iface_path, funcs) in map.iter() {
+        let Some(module_path) = module_paths.get(iface_path) else {
+            continue;
+        };
+        let client_name = format_ident!("{}HostClient", iface_names[iface_path]);
+        // Host functions are called with the same (possibly borrowed) types that they take
+        let type_lookup = TypeLookup {
+            paths: struct_lookup,
+            scope: Some(module_path),
+            owned_forms: &HashMap::new(),
+        };
+
+        let client_fns =
+            funcs.iter().map(|f| {
+                let func_name = &f.sig.ident;
+                let mut sig = f.sig.clone();
+
+                // Types defined by wit-bindgen must be referred to by their full path
+                for input in sig.inputs.iter_mut() {
+                    if let syn::FnArg::Typed(pat_type) = input {
+                        *pat_type.ty =
+                            syn::parse2(qualify_type(pat_type.ty.to_token_stream(), type_lookup))
+                                .unwrap_or_else(|_| (*pat_type.ty).clone());
+                    }
+                }
+                let args = f.sig.inputs.iter().filter_map(|input| match input {
+                    syn::FnArg::Typed(pat_type) => Some(pat_type.pat.clone()),
+                    syn::FnArg::Receiver(_) => None,
                 });
+
+                // Replace the error type of a Result with the provider's error type
+                let converts_error =
+                    if let ReturnType::Type(_, ty) = &mut sig.output {
+                        let qualified: syn::Type =
+                            syn::parse2(qualify_type(ty.to_token_stream(), type_lookup))
+                                .unwrap_or_else(|_| (**ty).clone());
+                        **ty = qualified;
+                        match &mut **ty {
+                            syn::Type::Path(p) => p.path.segments.last_mut().is_some_and(|seg| {
+                                match &mut seg.arguments {
+                                    syn::PathArguments::AngleBracketed(generics)
+                                        if seg.ident == "Result" && generics.args.len() == 2 =>
+                                    {
+                                        generics.args[1] =
+                                            syn::GenericArgument::Type(host_error.clone());
+                                        true
+                                    }
+                                    _ => false,
+                                }
+                            }),
+                            _ => false,
+                        }
+                    } else {
+                        false
+                    };
+
+                let call = quote::quote!(#module_path::#func_name(#(#args),*));
+                if converts_error {
+                    quote::quote!(
+                        pub #sig {
+                            #call.map_err(::core::convert::Into::into)
+                        }
+                    )
+                } else {
+                    quote::quote!(
+                        pub #sig {
+                            #call
+                        }
+                    )
+                }
+            });
+
+        tokens.append_all(quote::quote!(
+            /// Client for calling functions imported from the host, with errors
+            /// converted into the provider's error type
+            #allow_lints
+            pub struct #client_name;
+
+            #allow_lints
+            impl #client_name {
+                #(#client_fns)*
+            }
+        ));
+    }
+    tokens
+}
+
+/// Replace types defined by the wit-bindgen output with the full path to them, at any depth
+fn qualify_type(
+    ty: proc_macro2::TokenStream,
+    struct_lookup: TypeLookup<'_>,
+) -> proc_macro2::TokenStream {
+    let mut tokens = proc_macro2::TokenStream::new();
+    let mut after_path_sep = false;
+    for tt in ty {
+        let is_path_sep = matches!(&tt, TokenTree::Punct(p) if p.as_char() == ':');
+        match tt {
+            // Only bare identifiers are replaced, as ones following a `::` are already qualified
+            TokenTree::Ident(ref i) if !after_path_sep => match struct_lookup.get(&i.to_string()) {
+                Some(path) => path.to_tokens(&mut tokens),
+                None => tokens.append(tt),
+            },
+            TokenTree::Group(ref g) => {
+                let mut group =
+                    proc_macro2::Group::new(g.delimiter(), qualify_type(g.stream(), struct_lookup));
+                group.set_span(g.span());
+                tokens.append(group);
+            }
+            tt => tokens.append(tt),
+        }
+        after_path_sep = is_path_sep;
+    }
+    tokens
+}
+
+/// Get the type of the value that a function successfully returns, i.e. `T` for functions that return
+/// `Result<T, E>`, and the return type itself otherwise
+fn result_ok_type(ret: &ReturnType, struct_lookup: TypeLookup<'_>) -> syn::Type {
+    let ReturnType::Type(_, ty) = ret else {
+        return syn::parse_quote!(());
+    };
+    let ty: syn::Type = syn::parse2(qualify_type(ty.to_token_stream(), struct_lookup))
+        .unwrap_or_else(|_| (**ty).clone());
+    if let syn::Type::Path(p) = &ty {
+        if let Some(syn::PathSegment {
+            ident,
+            arguments: syn::PathArguments::AngleBracketed(args),
+        }) = p.path.segments.last()
+        {
+            if ident == "Result" {
+                if let Some(syn::GenericArgument::Type(ok)) = args.args.first() {
+                    return ok.clone();
+                }
+            }
         }
     }
-    methods_by_name
+    ty
 }
 
-// no-op when not in debug mode
-#[cfg(not(feature = "debug"))]
-fn debug_print(_s: impl AsRef<str>) {}
+/// Get the error type of a function that returns `Result<T, E>`
+fn result_err_type(ret: &ReturnType) -> Option<&syn::Type> {
+    let ReturnType::Type(_, ty) = ret else {
+        return None;
+    };
+    let syn::Type::Path(p) = ty.as_ref() else {
+        return None;
+    };
+    match p.path.segments.last()? {
+        syn::PathSegment {
+            ident,
+            arguments: syn::PathArguments::AngleBracketed(args),
+        } if ident == "Result" => match args.args.iter().nth(1)? {
+            syn::GenericArgument::Type(err) => Some(err),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Convert a list of key/value pairs (ex. `Vec<(String, String)>`, as WIT models maps as `list<tuple<K, V>>`)
+/// into a `HashMap`, if the key type is known to be hashable
+fn map_list_type(
+    ty: &proc_macro2::TokenStream,
+    string_type: &syn::Type,
+) -> Option<proc_macro2::TokenStream> {
+    /// Types that are known to implement `Hash` and `Eq`
+    const HASHABLE_TYPES: &[&str] = &[
+        "String", "bool", "char", "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64",
+    ];
+
+    let syn::Type::Path(list) = syn::parse2::<syn::Type>(ty.clone()).ok()? else {
+        return None;
+    };
+    let list_segment = list.path.segments.last()?;
+    let syn::PathArguments::AngleBracketed(args) = &list_segment.arguments else {
+        return None;
+    };
+    if list_segment.ident != "Vec" || args.args.len() != 1 {
+        return None;
+    }
+    let Some(syn::GenericArgument::Type(syn::Type::Tuple(pair))) = args.args.first() else {
+        return None;
+    };
+    let (Some(key), Some(value), 2) = (pair.elems.first(), pair.elems.last(), pair.elems.len())
+    else {
+        return None;
+    };
+
+    // Types are matched by their name regardless of their path (ex. `wit_bindgen::rt::string::String`)
+    let is_hashable = key.to_token_stream().to_string()
+        == string_type.to_token_stream().to_string()
+        || matches!(
+            key,
+            syn::Type::Path(p) if p.qself.is_none() && p.path.segments.last().is_some_and(|s| {
+                s.arguments.is_none() && HASHABLE_TYPES.contains(&s.ident.to_string().as_str())
+            })
+        );
+    is_hashable.then(|| quote::quote!(::std::collections::HashMap<#key, #value>))
+}
+
+/// Check whether an (owned) type is made up solely of types known to implement `Default`
+fn is_default_type(ty: proc_macro2::TokenStream) -> bool {
+    /// Identifiers (types and the paths to them) that are known to implement `Default`
+    const DEFAULT_IDENTS: &[&str] = &[
+        "bool",
+        "u8",
+        "u16",
+        "u32",
+        "u64",
+        "i8",
+        "i16",
+        "i32",
+        "i64",
+        "f32",
+        "f64",
+        "char",
+        "String",
+        "Vec",
+        "Option",
+        "Box",
+        "HashMap",
+        "std",
+        "alloc",
+        "core",
+        "collections",
+        "string",
+        "vec",
+        "option",
+        "boxed",
+        "wit_bindgen",
+        "rt",
+    ];
+    ty.into_iter().all(|tt| match tt {
+        TokenTree::Ident(i) => DEFAULT_IDENTS.contains(&i.to_string().as_str()),
+        TokenTree::Group(g) => is_default_type(g.stream()),
+        TokenTree::Punct(_) | TokenTree::Literal(_) => true,
+    })
+}
+
+/// Full path of a type as a string (ex. `wasmcloud::keyvalue::types::Metadata`),
+/// used to key the types that were extended with serde derives
+fn path_key(path: &Punctuated<PathSegment, PathSep>) -> String {
+    path.iter()
+        .map(|s| s.ident.to_string())
+        .collect::<Vec<String>>()
+        .join("::")
+}
+
+/// Lookup of the full paths of the types that were extended with serde derives,
+/// as seen from the module of a specific interface
+#[derive(Clone, Copy)]
+struct TypeLookup<'a> {
+    /// Full paths of extended types, keyed by [`path_key`]
+    paths: &'a HashMap<String, Punctuated<PathSegment, PathSep>>,
+    /// Path to the module of the interface that types are being referred to from, if known
+    scope: Option<&'a Punctuated<PathSegment, PathSep>>,
+    /// Names of the owned forms of borrowed records, keyed by [`path_key`] of the borrowed form
+    owned_forms: &'a HashMap<String, String>,
+}
+
+impl<'a> TypeLookup<'a> {
+    /// Get the full path of the type with the given name
+    ///
+    /// Types defined by the interface itself take precedence, otherwise the name must
+    /// belong to a type defined by exactly one other interface (ex. one that was `use`d)
+    fn get(&self, name: &str) -> Option<&'a Punctuated<PathSegment, PathSep>> {
+        if let Some(path) = self
+            .scope
+            .and_then(|scope| self.paths.get(&format!("{}::{name}", path_key(scope))))
+        {
+            return Some(path);
+        }
+
+        let mut matches = self
+            .paths
+            .values()
+            .filter(|p| p.last().is_some_and(|s| s.ident == name));
+        match (matches.next(), matches.next()) {
+            (Some(path), None) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Get the full path of the owned form of the borrowed record with the given name, if it has one
+    fn get_owned_form(&self, name: &str) -> Option<&'a Punctuated<PathSegment, PathSep>> {
+        let owned_name = self.owned_forms.get(&path_key(self.get(name)?))?;
+        self.get(owned_name)
+    }
+}
+
+/// Convert a single identifier type `T` (ex. the `T` of a `&T` argument) into an owned type usable as an
+/// invocation struct member
+fn owned_ref_type(
+    t: &Ident,
+    struct_lookup: TypeLookup<'_>,
+    string_type: &syn::Type,
+) -> proc_macro2::TokenStream {
+    match t.to_string().as_str() {
+        // A &str is replaced with an owned string
+        "str" => string_type.to_token_stream(),
+
+        // References to the Rust types of WIT primitives (ex. `&char`, `&u32`) are replaced with the (Copy) type itself,
+        // without looking them up as types generated by wit-bindgen
+        "bool" | "char" | "u8" | "u16" | "u32" | "u64" | "i8" | "i16" | "i32" | "i64" | "f32"
+        | "f64" => t.to_token_stream(),
+
+        // Unexpected non-standard type as reference (likely a known custom type generated by wit-bindgen)
+        //
+        // If we have a T that this module defined, we must use the full path to it
+        // if not, it is likely a builtin, so we can use it directly
+        name => struct_lookup
+            .get(name)
+            .map_or_else(|| t.to_token_stream(), ToTokens::to_token_stream),
+    }
+}
+
+/// Convert a (possibly borrowed) type into an owned type usable as an invocation struct member
+///
+/// References are stripped (converting to the owned equivalent, ex. `&str` -> `String`, `&[T]` -> `Vec<T>`)
+/// at any depth, recurring into the elements of tuples and generic arguments, and types defined by
+/// the wit-bindgen output are replaced with the full path to them. Since the path substitution
+/// happens at every depth, types nested in wrappers (ex. `Option<Box<T>>`) are qualified too.
+fn owned_type(
+    ty: &[TokenTree],
+    struct_lookup: TypeLookup<'_>,
+    string_type: &syn::Type,
+) -> proc_macro2::TokenStream {
+    match ty {
+        // &'a T
+        [TokenTree::Punct(p), TokenTree::Punct(l), TokenTree::Ident(_), rest @ ..]
+            if p.as_char() == '&' && l.as_char() == '\'' =>
+        {
+            owned_type(&[&ty[..1], rest].concat(), struct_lookup, string_type)
+        }
+
+        // &mut T
+        [TokenTree::Punct(p), TokenTree::Ident(m), rest @ ..]
+            if p.as_char() == '&' && m == "mut" =>
+        {
+            owned_type(&[&ty[..1], rest].concat(), struct_lookup, string_type)
+        }
+
+        // &T
+        [TokenTree::Punct(p), rest @ ..] if p.as_char() == '&' => match rest {
+            [TokenTree::Ident(t)] => owned_ref_type(t, struct_lookup, string_type),
+
+            // A slice (ex. &[u8]) is replaced with an owned Vec
+            [TokenTree::Group(g)] if g.delimiter() == Delimiter::Bracket => {
+                let elem = owned_type(
+                    &g.stream().into_iter().collect::<Vec<TokenTree>>(),
+                    struct_lookup,
+                    string_type,
+                );
+                quote::quote!(Vec<#elem>)
+            }
+
+            rest => owned_type(rest, struct_lookup, string_type),
+        },
+
+        // T, a type that this module defined or a builtin
+        [TokenTree::Ident(t)] => owned_ref_type(t, struct_lookup, string_type),
+
+        // (A, B, ...)
+        [TokenTree::Group(g)] if g.delimiter() == Delimiter::Parenthesis => {
+            let elems = split_top_level_commas(g.stream())
+                .iter()
+                .map(|elem| owned_type(elem, struct_lookup, string_type))
+                .collect::<Vec<proc_macro2::TokenStream>>();
+            quote::quote!((#(#elems,)*))
+        }
+
+        // T<'a>, the borrowed form of a record, which is replaced with its owned form
+        // (the lifetime may be followed by a trailing comma, ex. `T<'_,>` as emitted by wit-bindgen)
+        [TokenTree::Ident(t), TokenTree::Punct(open), TokenTree::Punct(l), TokenTree::Ident(_), rest @ ..]
+            if open.as_char() == '<'
+                && l.as_char() == '\''
+                && match rest {
+                    [TokenTree::Punct(close)] => close.as_char() == '>',
+                    [TokenTree::Punct(comma), TokenTree::Punct(close)] => {
+                        comma.as_char() == ',' && close.as_char() == '>'
+                    }
+                    _ => false,
+                }
+                && struct_lookup.get_owned_form(&t.to_string()).is_some() =>
+        {
+            struct_lookup
+                .get_owned_form(&t.to_string())
+                .map(ToTokens::to_token_stream)
+                .unwrap_or_default()
+        }
+
+        // Wrapper<A, B, ...>, where the wrapper may be a (fully qualified) path, which is kept as-is
+        // while its arguments are converted (ex. 'wit_bindgen::rt::vec::Vec<&str>', as emitted by older
+        // versions of wit-bindgen, becomes 'wit_bindgen::rt::vec::Vec<String>')
+        [.., TokenTree::Punct(close)] if close.as_char() == '>' => {
+            let Some(open) = ty
+                .iter()
+                .position(|tt| matches!(tt, TokenTree::Punct(p) if p.as_char() == '<'))
+            else {
+                return proc_macro2::TokenStream::from_iter(ty.iter().cloned());
+            };
+            let wrapper = proc_macro2::TokenStream::from_iter(ty[..open].iter().cloned());
+            let args = split_top_level_commas(proc_macro2::TokenStream::from_iter(
+                ty[open + 1..ty.len() - 1].iter().cloned(),
+            ))
+            .iter()
+            .map(|arg| owned_type(arg, struct_lookup, string_type))
+            .collect::<Vec<proc_macro2::TokenStream>>();
+            quote::quote!(#wrapper<#(#args),*>)
+        }
+
+        ty => proc_macro2::TokenStream::from_iter(ty.iter().cloned()),
+    }
+}
+
+/// Environment variable that enables debug output at macro expansion time (ex. `WASMCLOUD_MACRO_DEBUG=1`),
+/// without rebuilding the macro with its `debug` feature
+const DEBUG_ENV_VAR: &str = "WASMCLOUD_MACRO_DEBUG";
 
-#[cfg(feature = "debug")]
-fn debug_print(s: impl AsRef<str> + std::fmt::Display) {
-    eprintln!("DEBUG: {}", s);
+/// Check whether debug output is enabled, either by the `debug` feature or by [`DEBUG_ENV_VAR`]
+///
+/// The variable is read on every check rather than once, as the macro may be loaded by a long-lived
+/// process (ex. rust-analyzer) whose environment changes between expansions
+fn debug_enabled() -> bool {
+    cfg!(feature = "debug")
+        || std::env::var(DEBUG_ENV_VAR).is_ok_and(|v| !v.is_empty() && v != "0" && v != "false")
 }
+
+#[cfg(test)]
+mod tests;
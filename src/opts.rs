@@ -0,0 +1,1030 @@
+//! Options specific to wasmCloud provider generation, which can be supplied to `generate!`
+//! alongside regular wit-bindgen options.
+//!
+//! wasmCloud options are only recognized when the wit-bindgen args are supplied in their braced form
+//! (ex. `{ world: "provider", path: "wit", flatten_fields: ["config.inner"] }`), and are removed
+//! from the args before they are handed to wit-bindgen.
+
+use std::collections::{HashMap, HashSet};
+
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use proc_macro2::{Delimiter, Group, Punct, Spacing, TokenStream, TokenTree};
+use quote::TokenStreamExt;
+use syn::parse::{Error, Parse, ParseStream, Result};
+use syn::punctuated::Punctuated;
+use syn::{Ident, Lit, LitBool, LitInt, LitStr, Meta, Path, Token, Type};
+
+/// Names of options of later versions of wit-bindgen, which the version in use does not support
+const UNSUPPORTED_BINDGEN_OPT_NAMES: &[&str] = &["with", "generate_all"];
+
+/// wasmCloud-specific options that control provider generation
+#[derive(Default)]
+pub(crate) struct ProviderOpts {
+    /// Record fields that should be marked `#[serde(flatten)]`, stored as
+    /// (<UpperCamelCase struct name>, <snake_case field name>) pairs
+    pub(crate) flatten_fields: HashSet<(String, String)>,
+
+    /// How the generated per-interface traits express async methods
+    pub(crate) trait_style: TraitStyle,
+
+    /// Owned type to use in place of `String` for string arguments
+    pub(crate) string_type: Option<Type>,
+
+    /// Whether to generate a criterion benchmark harness for dispatch
+    /// (gated behind the provider's `bench` feature)
+    pub(crate) bench_harness: bool,
+
+    /// Condition under which the benchmark harness is built, in place of the provider's `bench` feature
+    pub(crate) bench_cfg: Option<Meta>,
+
+    /// Maximum number of concurrent executions of specific methods,
+    /// keyed by snake_case function name
+    pub(crate) method_concurrency: HashMap<String, usize>,
+
+    /// Methods (by snake_case function name) whose invocations must be authorized
+    /// by the provider before being dispatched
+    pub(crate) authorize_methods: HashSet<String>,
+
+    /// Provider error type that errors from calls to imported host functions are converted into
+    pub(crate) host_error: Option<Type>,
+
+    /// Whether to additionally generate a free function that mirrors `dispatch`
+    pub(crate) dispatch_fn: bool,
+
+    /// Name of the free function that mirrors `dispatch`, when it isn't named after the provider
+    pub(crate) dispatch_fn_name: Option<Ident>,
+
+    /// Format of the method names that invocations are received with
+    pub(crate) lattice_method_style: LatticeMethodStyle,
+
+    /// Whether the names of generated invocation structs include the version of versioned packages
+    pub(crate) versioned_struct_names: bool,
+
+    /// Function that incoming method names are passed through before being dispatched
+    pub(crate) method_rewrite: Option<Path>,
+
+    /// Format that invocations and their results are (de)serialized with
+    pub(crate) wire_format: WireFormat,
+
+    /// Whether to generate a client per interface for invoking its methods on other entities over the lattice
+    pub(crate) lattice_clients: bool,
+
+    /// Whether errors of types defined by the WIT are serialized as part of results, rather than stringified
+    pub(crate) structured_errors: bool,
+
+    /// Whether the argument of single-argument methods is received directly, rather than in an invocation struct
+    pub(crate) unwrap_single_args: bool,
+
+    /// Whether to generate the source of an impl block with every method the provider must implement
+    pub(crate) method_skeleton: bool,
+
+    /// Whether arguments that are lists of key/value pairs are received as a `HashMap`
+    pub(crate) map_lists: bool,
+
+    /// Type that the config values of links are decoded into before being passed to the provider
+    pub(crate) config: Option<Type>,
+
+    /// Whether the config values of links are passed to the provider as a `HashMap<String, String>`
+    pub(crate) config_map: bool,
+
+    /// Whether to generate a `start()` method that runs the provider
+    pub(crate) entrypoint: bool,
+
+    /// Whether the provider implements `MessageDispatch` itself, rather than it being generated
+    pub(crate) custom_dispatch: bool,
+
+    /// How generated invocation structs are named
+    pub(crate) struct_naming: StructNaming,
+
+    /// Derives added to the generated invocation structs
+    pub(crate) additional_derives: Vec<Path>,
+
+    /// Module name of the WIT namespace whose packages are handled, rather than the detected one
+    pub(crate) namespace: Option<String>,
+
+    /// Module name of the WIT package (without version) whose interfaces are handled, rather than every detected one
+    pub(crate) package: Option<String>,
+
+    /// Whether to generate round-trip tests of the wire format of invocation structs
+    pub(crate) generate_tests: bool,
+
+    /// Methods (by snake_case function name) whose invocations are received and
+    /// responded to as raw bytes, without (de)serialization
+    pub(crate) raw_methods: HashSet<String>,
+
+    /// Suffix of the names of the generated per-interface traits
+    pub(crate) trait_suffix: String,
+
+    /// Whether to generate a constant describing the interfaces and operations of the provider
+    pub(crate) contract_metadata: bool,
+
+    /// Interfaces (by snake_case name) that are served by the provider, when not all of them are
+    pub(crate) only_interfaces: HashSet<String>,
+
+    /// Interfaces (by snake_case name) that are not served by the provider
+    pub(crate) skip_interfaces: HashSet<String>,
+
+    /// Type implementing the generated `InvocationCodec` trait, that invocations and their results are
+    /// (de)serialized with rather than the wire format
+    pub(crate) codec: Option<Path>,
+
+    /// Features of the provider that the generated code of interfaces is gated behind, by snake_case interface
+    /// name (ex. `key_value`), optionally qualified by its package (ex. `keyvalue/key_value`)
+    pub(crate) interface_features: HashMap<String, String>,
+
+    /// Whether the futures of the per-interface traits are not required to be `Send`
+    pub(crate) local_futures: bool,
+
+    /// Methods (by snake_case function name) that the provider's author has acknowledged handling, which must
+    /// match the methods of the handled interfaces exactly, if given
+    pub(crate) acknowledged_methods: Option<HashSet<String>>,
+
+    /// Type that the context of each invocation is converted into, which is passed to the provider's methods
+    pub(crate) claims: Option<Path>,
+
+    /// Whether the code generated for each interface is written to a file in `OUT_DIR` and included from it,
+    /// when debug output is enabled
+    pub(crate) split_output: bool,
+
+    /// Whether the provider's methods (and the per-interface traits) are async or blocking
+    pub(crate) mode: MethodMode,
+
+    /// Path to the `wasmcloud_provider_sdk` crate, when it is renamed or re-exported (ex. `::my_prelude::sdk`)
+    pub(crate) sdk_path: Option<Path>,
+
+    /// Path to the `serde` crate, when it is renamed or re-exported
+    pub(crate) serde_path: Option<Path>,
+
+    /// Path to the `async_trait` crate, when it is renamed or re-exported
+    pub(crate) async_trait_path: Option<Path>,
+
+    /// Whether invocations and records with fields that are not in the WIT fail to deserialize,
+    /// rather than the unknown fields being ignored
+    pub(crate) deny_unknown_fields: bool,
+
+    /// Whether fields of records and invocations are (de)serialized with their original WIT (kebab-case) names,
+    /// rather than their Rust (snake_case) ones
+    pub(crate) wit_field_names: bool,
+}
+
+/// Style of async trait that is generated for each WIT interface
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TraitStyle {
+    /// Traits and impls are annotated with `#[async_trait]` (boxed futures)
+    #[default]
+    AsyncTrait,
+
+    /// Traits use native `async fn`, with a `Send`-bounded variant produced by `trait_variant::make`
+    TraitVariant,
+
+    /// Trait methods explicitly return `Pin<Box<dyn Future>>`, leaving boxing in the implementer's control
+    BoxedFuture,
+}
+
+/// Format of the method names that are used for invocations on the lattice
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LatticeMethodStyle {
+    /// `Message.<UpperCamelCase function>` (ex. `Message.Get`)
+    #[default]
+    Legacy,
+
+    /// `<ns>:<package>/<interface>.<function>` (ex. `wasmcloud:keyvalue/key-value.get`)
+    Wit,
+}
+
+/// Naming scheme of the generated invocation structs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StructNaming {
+    /// `<Package><Interface><Function>Invocation` (ex. `KeyvalueStoreGetInvocation`)
+    #[default]
+    Full,
+
+    /// `<Interface><Function>Invocation` (ex. `StoreGetInvocation`)
+    Interface,
+
+    /// `<Function>Invocation` (ex. `GetInvocation`)
+    Short,
+}
+
+/// Whether the methods a provider implements for its interfaces are async or blocking
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MethodMode {
+    /// `async fn`s, awaited by dispatch
+    #[default]
+    Async,
+
+    /// Blocking `fn`s (ex. wrapping synchronous native libraries), called directly by dispatch
+    Sync,
+}
+
+/// Format that invocations and their results are (de)serialized with on the lattice
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WireFormat {
+    /// Whatever `wasmcloud_provider_sdk::{serialize, deserialize}` use
+    #[default]
+    Sdk,
+
+    /// MessagePack, via `rmp_serde`
+    Msgpack,
+
+    /// JSON, via `serde_json`
+    Json,
+}
+
+impl ProviderOpts {
+    /// Split wasmCloud-specific options out of the args meant for wit-bindgen,
+    /// returning the parsed options and the remaining wit-bindgen args
+    pub(crate) fn extract(bindgen_args: TokenStream) -> Result<(ProviderOpts, TokenStream)> {
+        let mut opts = ProviderOpts::default();
+
+        // Only the braced form of wit-bindgen args can carry options
+        let tokens = bindgen_args.into_iter().collect::<Vec<TokenTree>>();
+        let group = match &tokens[..] {
+            [TokenTree::Group(g)] if g.delimiter() == Delimiter::Brace => g,
+            _ => return Ok((opts, TokenStream::from_iter(tokens))),
+        };
+
+        // Split the braced contents into comma-separated fields, sorting them
+        // into ones we consume and ones that get forwarded to wit-bindgen
+        let mut forwarded = TokenStream::new();
+        let mut seen = HashSet::new();
+        for field in split_top_level_commas(group.stream()) {
+            let is_provider_opt = matches!(
+                field.first(),
+                Some(TokenTree::Ident(i)) if PROVIDER_OPT_NAMES.contains(&i.to_string().as_str())
+            );
+            if let Some(TokenTree::Ident(i)) = field
+                .first()
+                .filter(|tt| UNSUPPORTED_BINDGEN_OPT_NAMES.contains(&tt.to_string().as_str()))
+            {
+                return Err(Error::new(
+                    i.span(),
+                    format!("the `{i}` option is not supported by the version of wit-bindgen in use (0.9)"),
+                ));
+            }
+            if let Some(TokenTree::Ident(i)) = field.first().filter(|_| is_provider_opt) {
+                if !seen.insert(i.to_string()) {
+                    return Err(Error::new(i.span(), format!("option `{i}` given twice")));
+                }
+            }
+            if is_provider_opt {
+                opts.apply(syn::parse2::<ProviderOpt>(TokenStream::from_iter(field))?)?;
+            } else {
+                forwarded.append_all(field);
+                forwarded.append(Punct::new(',', Spacing::Alone));
+            }
+        }
+
+        let mut forwarded_group = Group::new(Delimiter::Brace, forwarded);
+        forwarded_group.set_span(group.span());
+        Ok((opts, TokenTree::Group(forwarded_group).into()))
+    }
+
+    /// Apply a single parsed option
+    fn apply(&mut self, opt: ProviderOpt) -> Result<()> {
+        match opt {
+            ProviderOpt::FlattenFields(List(list)) => {
+                for s in list {
+                    let value = s.value();
+                    let Some((struct_name, field_name)) = value.split_once('.') else {
+                        return Err(Error::new(
+                            s.span(),
+                            "invalid flattened field, expected `<record>.<field>` (ex. `config.inner`)",
+                        ));
+                    };
+                    self.flatten_fields.insert((
+                        struct_name.to_upper_camel_case(),
+                        field_name.to_snake_case(),
+                    ));
+                }
+            }
+            ProviderOpt::TraitStyle(s) => {
+                self.trait_style = choice(
+                    &s,
+                    "trait style",
+                    &[
+                        ("async_trait", TraitStyle::AsyncTrait),
+                        ("trait_variant", TraitStyle::TraitVariant),
+                        ("boxed_future", TraitStyle::BoxedFuture),
+                    ],
+                )?;
+            }
+            ProviderOpt::StringType(ty) => self.string_type = Some(ty),
+            ProviderOpt::BenchHarness(b) => self.bench_harness = b.value,
+            ProviderOpt::BenchCfg(cfg) => self.bench_cfg = Some(cfg),
+            ProviderOpt::MethodConcurrency(Map(entries)) => {
+                for MapEntry { key, value } in entries {
+                    self.method_concurrency
+                        .insert(key.value().to_snake_case(), value.base10_parse()?);
+                }
+            }
+            ProviderOpt::AuthorizeMethods(list) => {
+                self.authorize_methods.extend(snake_case_names(list))
+            }
+            ProviderOpt::HostError(ty) => self.host_error = Some(ty),
+            ProviderOpt::DispatchFn(Lit::Bool(b)) => self.dispatch_fn = b.value,
+            ProviderOpt::DispatchFn(Lit::Str(s)) => {
+                self.dispatch_fn = true;
+                self.dispatch_fn_name = Some(s.parse().map_err(|_| {
+                    Error::new(
+                        s.span(),
+                        "invalid dispatch function name, expected an identifier (ex. `handle_invocation`)",
+                    )
+                })?);
+            }
+            ProviderOpt::DispatchFn(lit) => {
+                return Err(Error::new(
+                    lit.span(),
+                    "expected `true`, `false` or a function name (ex. `\"handle_invocation\"`)",
+                ))
+            }
+            ProviderOpt::VersionedStructNames(b) => self.versioned_struct_names = b.value,
+            ProviderOpt::MethodRewrite(path) => self.method_rewrite = Some(path),
+            ProviderOpt::LatticeClients(b) => self.lattice_clients = b.value,
+            ProviderOpt::StructuredErrors(b) => self.structured_errors = b.value,
+            ProviderOpt::UnwrapSingleArgs(b) => self.unwrap_single_args = b.value,
+            ProviderOpt::MethodSkeleton(b) => self.method_skeleton = b.value,
+            ProviderOpt::MapLists(b) => self.map_lists = b.value,
+            ProviderOpt::Config(ty) => self.config = Some(ty),
+            ProviderOpt::ConfigMap(b) => self.config_map = b.value,
+            ProviderOpt::Entrypoint(b) => self.entrypoint = b.value,
+            ProviderOpt::Dispatch(b) => self.custom_dispatch = !b.value,
+            ProviderOpt::AdditionalDerives(List(paths)) => self.additional_derives.extend(paths),
+            // wit-bindgen generates modules with snake_case names (ex. `key-value` -> `key_value`)
+            ProviderOpt::Namespace(s) => self.namespace = Some(s.value().to_snake_case()),
+            ProviderOpt::Package(s) => self.package = Some(s.value().to_snake_case()),
+            ProviderOpt::GenerateTests(b) => self.generate_tests = b.value,
+            ProviderOpt::TraitSuffix(s) => {
+                let suffix = s.value();
+                // Without a suffix, the traits would share the names of the traits wit-bindgen generates
+                if suffix.is_empty()
+                    || !suffix
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    return Err(Error::new(
+                        s.span(),
+                        format!("invalid trait suffix: `{suffix}`; expected an identifier (ex. `Provider`)"),
+                    ));
+                }
+                self.trait_suffix = suffix;
+            }
+            ProviderOpt::ContractMetadata(b) => self.contract_metadata = b.value,
+            ProviderOpt::Codec(path) => self.codec = Some(path),
+            ProviderOpt::LocalFutures(b) => self.local_futures = b.value,
+            ProviderOpt::Claims(path) => self.claims = Some(path),
+            ProviderOpt::SplitOutput(b) => self.split_output = b.value,
+            ProviderOpt::SdkPath(path) => self.sdk_path = Some(crate_path(path)?),
+            ProviderOpt::SerdePath(path) => self.serde_path = Some(crate_path(path)?),
+            ProviderOpt::AsyncTraitPath(path) => self.async_trait_path = Some(crate_path(path)?),
+            ProviderOpt::DenyUnknownFields(b) => self.deny_unknown_fields = b.value,
+            ProviderOpt::WitFieldNames(b) => self.wit_field_names = b.value,
+            ProviderOpt::Mode(s) => {
+                self.mode = choice(
+                    &s,
+                    "mode",
+                    &[("async", MethodMode::Async), ("sync", MethodMode::Sync)],
+                )?;
+            }
+            ProviderOpt::AcknowledgedMethods(list) => {
+                self.acknowledged_methods = Some(snake_case_names(list).collect())
+            }
+            ProviderOpt::InterfaceFeatures(Map(entries)) => {
+                for MapEntry { key, value } in entries {
+                    let key = key
+                        .value()
+                        .split('/')
+                        .map(|part| part.to_snake_case())
+                        .collect::<Vec<String>>()
+                        .join("/");
+                    self.interface_features.insert(key, value.value());
+                }
+            }
+            ProviderOpt::OnlyInterfaces(list) => {
+                self.only_interfaces.extend(snake_case_names(list))
+            }
+            ProviderOpt::SkipInterfaces(list) => {
+                self.skip_interfaces.extend(snake_case_names(list))
+            }
+            ProviderOpt::RawMethods(list) => self.raw_methods.extend(snake_case_names(list)),
+            ProviderOpt::StructNaming(s) => {
+                self.struct_naming = choice(
+                    &s,
+                    "struct naming",
+                    &[
+                        ("full", StructNaming::Full),
+                        ("interface", StructNaming::Interface),
+                        ("short", StructNaming::Short),
+                    ],
+                )?;
+            }
+            ProviderOpt::WireFormat(s) => {
+                self.wire_format = choice(
+                    &s,
+                    "wire format",
+                    &[
+                        ("sdk", WireFormat::Sdk),
+                        ("msgpack", WireFormat::Msgpack),
+                        ("json", WireFormat::Json),
+                    ],
+                )?;
+            }
+            ProviderOpt::LatticeMethodStyle(s) => {
+                self.lattice_method_style = choice(
+                    &s,
+                    "lattice method style",
+                    &[
+                        ("legacy", LatticeMethodStyle::Legacy),
+                        ("wit", LatticeMethodStyle::Wit),
+                    ],
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Split a token stream on commas that are not nested inside generic arguments (i.e. `<...>`),
+/// dropping empty parts (i.e. the one after a trailing comma)
+pub(crate) fn split_top_level_commas(ts: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut parts = vec![Vec::new()];
+    let mut depth = 0usize;
+    for tt in ts {
+        let part = parts.last_mut().expect("parts are never empty");
+        match &tt {
+            TokenTree::Punct(p) if p.as_char() == '<' => depth += 1,
+            // The `>` of `->` (ex. in `Box<dyn Fn() -> T>`) does not close generic arguments
+            TokenTree::Punct(p)
+                if p.as_char() == '>'
+                    && !matches!(part.last(), Some(TokenTree::Punct(prev))
+                        if prev.as_char() == '-' && prev.spacing() == Spacing::Joint) =>
+            {
+                depth = depth.saturating_sub(1)
+            }
+            TokenTree::Punct(p) if p.as_char() == ',' && depth == 0 => {
+                parts.push(Vec::new());
+                continue;
+            }
+            _ => {}
+        }
+        part.push(tt);
+    }
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+/// Check that the path of a crate is absolute (ex. `::my_prelude::sdk` or `crate::deps::sdk`),
+/// as it is used from modules generated inside the provider's module as well
+fn crate_path(path: Path) -> Result<Path> {
+    if path.leading_colon.is_none() && path.segments.first().is_none_or(|s| s.ident != "crate") {
+        return Err(Error::new_spanned(
+            &path,
+            "crate paths must be absolute (ex. `::my_prelude::sdk` or `crate::deps::sdk`)",
+        ));
+    }
+    Ok(path)
+}
+
+/// A bracketed list option value (ex. `["get", "set"]`)
+struct List<T>(Vec<T>);
+
+impl<T: Parse> Parse for List<T> {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let contents;
+        syn::bracketed!(contents in input);
+        let list = Punctuated::<T, Token![,]>::parse_terminated(&contents)?;
+        Ok(List(list.into_iter().collect()))
+    }
+}
+
+/// A braced map option value (ex. `{ "get": 2 }`)
+struct Map<V>(Vec<MapEntry<V>>);
+
+impl<V: Parse> Parse for Map<V> {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let contents;
+        syn::braced!(contents in input);
+        let entries = Punctuated::<MapEntry<V>, Token![,]>::parse_terminated(&contents)?;
+        Ok(Map(entries.into_iter().collect()))
+    }
+}
+
+/// Select the value of an option that is one of a fixed set of choices, named in its error as `what`
+fn choice<T: Copy>(s: &LitStr, what: &str, choices: &[(&str, T)]) -> Result<T> {
+    let value = s.value();
+    if let Some((_, choice)) = choices.iter().find(|(name, _)| *name == value) {
+        return Ok(*choice);
+    }
+    let names = choices
+        .iter()
+        .map(|(name, _)| format!("`{name}`"))
+        .collect::<Vec<String>>();
+    let (last, rest) = names.split_last().expect("choices are never empty");
+    Err(Error::new(
+        s.span(),
+        format!(
+            "unrecognized {what}: `{value}`; expected {} or {last}",
+            rest.join(", ")
+        ),
+    ))
+}
+
+/// snake_case names of the methods or interfaces given in a list option
+fn snake_case_names(list: List<LitStr>) -> impl Iterator<Item = String> {
+    list.0.into_iter().map(|s| s.value().to_snake_case())
+}
+
+/// A single `"key": value` entry of a map-like option
+struct MapEntry<V> {
+    key: LitStr,
+    value: V,
+}
+
+impl<V: Parse> Parse for MapEntry<V> {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let key = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let value = input.parse()?;
+        Ok(MapEntry { key, value })
+    }
+}
+
+/// Declare the wasmCloud-specific options, as documented `name => Variant(value type)` entries, generating the
+/// keyword, [`ProviderOpt`] variant and parsing of each (as `name: value`), along with [`PROVIDER_OPT_NAMES`]
+macro_rules! provider_opts {
+    ($($(#[$attr:meta])* $name:ident => $variant:ident($value:ty),)*) => {
+        mod kw {
+            $(syn::custom_keyword!($name);)*
+        }
+
+        /// Names of options that are consumed by this crate rather than wit-bindgen
+        const PROVIDER_OPT_NAMES: &[&str] = &[$(stringify!($name)),*];
+
+        /// A wasmCloud-specific option, which may be mixed in with the braced form of the wit-bindgen args
+        /// (ex. `{ world: "provider", path: "wit", trait_style: "trait_variant" }`)
+        enum ProviderOpt {
+            $(
+                $(#[$attr])*
+                $variant($value),
+            )*
+        }
+
+        impl Parse for ProviderOpt {
+            fn parse(input: ParseStream<'_>) -> Result<Self> {
+                let l = input.lookahead1();
+                $(
+                    if l.peek(kw::$name) {
+                        input.parse::<kw::$name>()?;
+                        input.parse::<Token![:]>()?;
+                        return Ok(ProviderOpt::$variant(input.parse()?));
+                    }
+                )*
+                Err(l.error())
+            }
+        }
+    };
+}
+
+provider_opts! {
+    /// `flatten_fields: ["<record>.<field>", ...]` - mark record fields as `#[serde(flatten)]`, so that
+    /// an embedded record can be received from actors that send a flat payload. Note that records with
+    /// flattened fields can only be decoded from map (named) encodings, rather than array (positional) ones
+    flatten_fields => FlattenFields(List<LitStr>),
+
+    /// `trait_style: "async_trait" | "trait_variant" | "boxed_future"` - how the generated per-interface
+    /// traits are declared. `async_trait` (the default) uses `#[async_trait]`, `trait_variant` uses native
+    /// `async fn` with a `Send`-bounded trait produced by [`trait_variant::make`](https://docs.rs/trait-variant)
+    /// (requires `trait-variant` as a dependency of the provider), and `boxed_future` declares methods
+    /// returning `Pin<Box<dyn Future + Send>>` explicitly
+    trait_style => TraitStyle(LitStr),
+
+    /// `string_type: path::to::Type` - owned type used in place of `String` for string arguments in
+    /// invocation structs (ex. `Box<str>`, `compact_str::CompactString`). The type must implement
+    /// `serde::Serialize`, `serde::Deserialize` and be usable where the provider expects a `String`
+    string_type => StringType(Type),
+
+    /// `bench_harness: true` - generate a `dispatch_bench` module (only built with the provider's `bench`
+    /// feature) containing a `bench_<method>` function per method, which measures `dispatch` throughput
+    /// for a representative invocation using [`criterion`](https://docs.rs/criterion) and `tokio`
+    bench_harness => BenchHarness(LitBool),
+
+    /// `bench_cfg: <cfg predicate>` - condition under which the `dispatch_bench` module is built, in place of
+    /// the provider's `bench` feature (ex. `bench_cfg: all(feature = "bench", not(target_arch = "wasm32"))`)
+    bench_cfg => BenchCfg(Meta),
+
+    /// `method_concurrency: { "<function>": <limit>, ... }` - limit the number of concurrent executions of
+    /// the named methods, with the dispatch of each one waiting on a `tokio::sync::Semaphore` that is created
+    /// on first use (requires `tokio` as a dependency of the provider). The semaphores are process-wide statics
+    /// (the macro cannot add fields to the provider), so the limit is shared by every instance of the provider,
+    /// and by every instantiation of a generic provider
+    method_concurrency => MethodConcurrency(Map<LitInt>),
+
+    /// `authorize_methods: ["<function>", ...]` - require invocations of the named methods to be authorized
+    /// before they are dispatched, by calling a method the provider must implement:
+    /// `async fn _authorize(&self, ctx: &wasmcloud_provider_sdk::Context, method: &str) -> bool`
+    /// (ex. checking `ctx.actor` against an allowlist). Unauthorized invocations receive an error
+    authorize_methods => AuthorizeMethods(List<LitStr>),
+
+    /// `host_error: path::to::Error` - generate a `<Interface>HostClient` per interface, with methods that call
+    /// the functions imported from the host, converting the error of any `Result` they return into the given
+    /// type (which must implement `From` for the original error type) so `?` can be used in provider code
+    host_error => HostError(Type),
+
+    /// `dispatch_fn: true | "<function>"` - additionally generate a free function that mirrors `dispatch`, which can be
+    /// called without going through the `MessageDispatch` trait (ex. from a component export). It is named after the
+    /// provider unless a name is given (ex. `handle_your_provider` for `YourProvider`), with the `Context` and errors
+    /// of the SDK in use (see `sdk_path`):
+    /// `async fn handle_your_provider(provider: &YourProvider, ctx: Context, method: String, body: &[u8]) -> Result<Vec<u8>, ProviderInvocationError>`
+    dispatch_fn => DispatchFn(Lit),
+
+    /// `lattice_method_style: "legacy" | "wit"` - format of the method names that invocations are received with.
+    /// `legacy` (the default) uses `Message.<Function>` (ex. `Message.Get`), while `wit` uses the fully
+    /// qualified WIT interface (ex. `wasmcloud:keyvalue/key-value.get`)
+    lattice_method_style => LatticeMethodStyle(LitStr),
+
+    /// `versioned_struct_names: true` - include the version of versioned WIT packages (ex. `wasmcloud:keyvalue@0.2.0`)
+    /// in the names of generated invocation structs (ex. `KeyvalueV020KeyValueGetInvocation`), which allows
+    /// a provider to support more than one version of the same package
+    versioned_struct_names => VersionedStructNames(LitBool),
+
+    /// `method_rewrite: path::to::function` - remap the method name of each incoming invocation before it is
+    /// dispatched (ex. stripping a routing prefix, resolving aliases), by calling the given function with
+    /// the method name. The function must take a `&str` and return something that converts `Into<String>`
+    /// (ex. `fn(&str) -> Cow<'_, str>`). By default, method names are dispatched as-is
+    method_rewrite => MethodRewrite(Path),
+
+    /// `wire_format: "sdk" | "msgpack" | "json"` - format that invocations and their results are (de)serialized with.
+    /// `sdk` (the default) uses `wasmcloud_provider_sdk::{serialize, deserialize}`, while `msgpack` uses
+    /// [`rmp_serde`](https://docs.rs/rmp-serde) and `json` uses [`serde_json`](https://docs.rs/serde_json),
+    /// which must be dependencies of the provider. The serde derives of generated types are the same for all formats
+    wire_format => WireFormat(LitStr),
+
+    /// `lattice_clients: true` - generate a `<Interface>LatticeClient` per interface, which sends invocations of
+    /// its methods to another entity on the lattice (ex. another provider implementing the same interface)
+    /// and decodes their results
+    lattice_clients => LatticeClients(LitBool),
+
+    /// `structured_errors: true` - for methods returning a `Result` whose error type is defined by the WIT
+    /// (ex. a `variant`), respond with the whole serialized `Result` rather than a stringified error, so callers
+    /// can reconstruct and branch on the typed error. Errors of other types (ex. `string`) are still stringified
+    structured_errors => StructuredErrors(LitBool),
+
+    /// `unwrap_single_args: true` - receive the argument of methods with exactly one argument directly
+    /// (ex. a bare string for `get(key: string)`), rather than wrapped in an invocation struct with a single field
+    unwrap_single_args => UnwrapSingleArgs(LitBool),
+
+    /// `method_skeleton: true` - generate a `PROVIDER_METHOD_SKELETON` constant containing the source of an
+    /// `impl YourProvider` block with every method the provider must implement (with `todo!()` bodies),
+    /// which can be copied into provider code when starting out or after the WIT changes
+    method_skeleton => MethodSkeleton(LitBool),
+
+    /// `map_lists: true` - receive arguments that are lists of key/value pairs (ex. `list<tuple<string, string>>`,
+    /// as WIT has no map type) as a `HashMap`, encoded as a map, when the key type is known to be hashable
+    map_lists => MapLists(LitBool),
+
+    /// `config: path::to::Config` - decode the config values of each link into the given type (via serde) before passing
+    /// the link to the provider, which must then implement
+    /// `async fn _put_link(&self, config: Config, ld: &wasmcloud_provider_sdk::core::LinkDefinition) -> bool`.
+    /// Links with config values that fail to decode are rejected, with the error logged via
+    /// [`tracing`](https://docs.rs/tracing) (which the provider must then depend on). As config values are strings,
+    /// the fields of the type must be decodable from strings
+    config => Config(Type),
+
+    /// `config_map: true` - pass the config values of each link as a `HashMap<String, String>`, for providers that
+    /// read a few values without a config type, which must then implement
+    /// `async fn _put_link(&self, config: HashMap<String, String>, ld: &wasmcloud_provider_sdk::core::LinkDefinition) -> bool`
+    config_map => ConfigMap(LitBool),
+
+    /// `entrypoint: true` - generate a `start()` function on the provider, which creates it and runs it with the SDK's
+    /// provider runtime (performing the handshake with the host), so that a provider's `main` can be as short as:
+    /// `YourProvider::start(Some("your-provider".into())).await`. The provider is created with its `Default` impl,
+    /// which providers holding state (ex. connection pools, config) must implement themselves (producing a compile
+    /// error otherwise); providers that cannot be created that way should call `wasmcloud_provider_sdk::run_provider`
+    entrypoint => Entrypoint(LitBool),
+
+    /// `dispatch: false` - skip generating the `MessageDispatch` impl, for providers that implement it themselves
+    /// (ex. for custom routing or batching), while keeping the invocation structs, per-interface traits and other impls.
+    /// The `Operation` enum (and everything else that dispatch uses) is not generated either
+    dispatch => Dispatch(LitBool),
+
+    /// `struct_naming: "full" | "interface" | "short"` - how generated invocation structs are named. `full` (the default)
+    /// uses `<Package><Interface><Function>Invocation`, `interface` uses `<Interface><Function>Invocation` and `short`
+    /// uses `<Function>Invocation`. Structs whose shorter name would collide with another type fall back to the full name
+    struct_naming => StructNaming(LitStr),
+
+    /// `additional_derives: [path::to::Derive, ...]` - derives added to the generated invocation structs
+    /// (ex. `schemars::JsonSchema`), which must be resolvable where `generate!` is invoked. Unlike the wit-bindgen
+    /// option of later versions, the types generated by wit-bindgen do not receive them
+    additional_derives => AdditionalDerives(List<Path>),
+
+    /// `namespace: "<ns>"` - the WIT namespace whose interfaces are handled, rather than the one detected from the
+    /// layout of the wit-bindgen output (by default the top level namespace), for worlds where detection picks the wrong
+    /// one. A namespace named `exports` (which is otherwise taken to be the module wit-bindgen generates for exported
+    /// interfaces) must be given this way
+    namespace => Namespace(LitStr),
+
+    /// `package: "<package>"` - the WIT package (without version) whose interfaces are handled, rather than every
+    /// package of the namespace, for worlds where detection picks the wrong ones
+    package => Package(LitStr),
+
+    /// `generate_tests: true` - generate a `#[cfg(test)]` module with a test per invocation struct that can be
+    /// default-constructed, which serializes a default instance, deserializes it and checks that serializing it again
+    /// produces identical bytes (comparing the encodings, as types generated by wit-bindgen do not implement `PartialEq`)
+    generate_tests => GenerateTests(LitBool),
+
+    /// `raw_methods: ["<function>", ...]` - functions whose invocations are received and responded to as raw bytes,
+    /// skipping (de)serialization (ex. for high-throughput blob transfers). Such functions must take a single `list<u8>`
+    /// argument (which receives the invocation's body) and return a `list<u8>` (optionally in a `result`, whose error
+    /// is stringified), which is sent as the response as-is
+    raw_methods => RawMethods(List<LitStr>),
+
+    /// `trait_suffix: "<Suffix>"` - suffix of the names of the generated per-interface traits (ex. `"Provider"` for
+    /// `KeyValueProvider`), for when the trait would otherwise clash with the trait wit-bindgen generates for the
+    /// same interface (when both are in scope)
+    trait_suffix => TraitSuffix(LitStr),
+
+    /// `contract_metadata: true` - generate a `PROVIDER_CONTRACT` constant containing a JSON description of the
+    /// interfaces handled by the provider (with their namespace, package and version) and the lattice methods of
+    /// each, for use by tooling (ex. build scripts generating wadm manifests or documentation)
+    contract_metadata => ContractMetadata(LitBool),
+
+    /// `only_interfaces: ["<interface>", ...]` - interfaces served by the provider, when it only handles some of the
+    /// interfaces of its world. Methods of other interfaces are not dispatched and need not be implemented
+    only_interfaces => OnlyInterfaces(List<LitStr>),
+
+    /// `skip_interfaces: ["<interface>", ...]` - interfaces of the world that are not served by the provider
+    skip_interfaces => SkipInterfaces(List<LitStr>),
+
+    /// `codec: path::to::Codec` - type that invocations and their results are (de)serialized with (ex. to compress,
+    /// encrypt or use a custom format), which must implement the generated `InvocationCodec` trait (with `encode` and
+    /// `decode` functions). Cannot be combined with `wire_format`
+    codec => Codec(Path),
+
+    /// `interface_features: { "<interface>": "<feature>", ... }` - gate the code generated for interfaces behind
+    /// features of the provider (ex. so an optional backend's interface is only served when it is built), with
+    /// their methods dispatched only when the feature is enabled. Interfaces may be qualified by their package
+    /// (ex. `"keyvalue/types"`), which takes precedence over the interface name alone, for interfaces that share
+    /// their name with an interface of another package
+    interface_features => InterfaceFeatures(Map<LitStr>),
+
+    /// `local_futures: true` - generate the per-interface traits with `#[async_trait(?Send)]`, for providers whose
+    /// methods' futures are not `Send` (ex. using `Rc` or `!Send` clients). As the SDK's `MessageDispatch` requires
+    /// `Send` futures, this requires `dispatch: false`, with the provider running invocations itself (ex. on a `LocalSet`)
+    local_futures => LocalFutures(LitBool),
+
+    /// `acknowledged_methods: ["<function>", ...]` - every method the provider handles, which must match the methods
+    /// of the handled interfaces exactly. A function added to (or removed from) the WIT then fails the build until its
+    /// method is implemented and acknowledged, rather than going unnoticed
+    acknowledged_methods => AcknowledgedMethods(List<LitStr>),
+
+    /// `claims: path::to::Claims` - convert the context of each invocation into the given type (ex. the caller's
+    /// identity), which is passed to every method after `ctx` (ex. `async fn get(&self, ctx: Context, claims: Claims,
+    /// key: String)`). The type must implement `TryFrom<&wasmcloud_provider_sdk::Context>` with an error implementing
+    /// `Display`, and invocations whose context fails to convert are rejected without invoking the method
+    claims => Claims(Path),
+
+    /// `split_output: true` - when debug output is enabled (with the macro's `debug` feature or
+    /// `WASMCLOUD_MACRO_DEBUG=1`), write the code generated for each interface to a file in `OUT_DIR`, which is
+    /// `include!`d rather than inlined, so that it can be inspected (and reported in errors) as a real file. Files are
+    /// named after the provider, the interface and a hash of their contents (ex.
+    /// `wasmcloud_your_provider_key_value_0123456789abcdef.rs`), so names are deterministic and providers of the same
+    /// name in different modules don't overwrite each other's files. Without debug output, the code is inlined as
+    /// usual, so normal builds never write to `OUT_DIR`. Requires the provider to have a build script, as `OUT_DIR` is
+    /// otherwise not set
+    split_output => SplitOutput(LitBool),
+
+    /// `mode: "async" | "sync"` - whether the methods the provider implements are `async fn`s (the default) or blocking
+    /// `fn`s (ex. for providers wrapping synchronous native libraries), which dispatch calls directly and the
+    /// per-interface traits declare without `async_trait`. This includes `_put_link()`, `_delete_link()` and
+    /// `_shutdown()`, while the optional hooks (ex. `_health_request()`) are always `async fn`s
+    mode => Mode(LitStr),
+
+    /// `sdk_path: ::path::to::sdk` - path that generated code uses for the `wasmcloud_provider_sdk` crate (by default
+    /// `::wasmcloud_provider_sdk`), for providers that rename it or depend on it through a facade crate (ex.
+    /// `sdk_path: crate::prelude::sdk`). The path must be absolute (starting with `::` or `crate::`)
+    sdk_path => SdkPath(Path),
+
+    /// `serde_path: ::path::to::serde` - path that generated code uses for the `serde` crate (by default
+    /// `::serde`), for providers that rename it or depend on it through a facade crate (ex.
+    /// `serde_path: crate::prelude::serde`). The path must be absolute (starting with `::` or `crate::`)
+    serde_path => SerdePath(Path),
+
+    /// `async_trait_path: ::path::to::async_trait` - path that generated code uses for the `async_trait` crate (by default
+    /// `::async_trait`), for providers that rename it or depend on it through a facade crate (ex.
+    /// `async_trait_path: crate::prelude::async_trait`). The path must be absolute (starting with `::` or `crate::`)
+    async_trait_path => AsyncTraitPath(Path),
+
+    /// `deny_unknown_fields: true` - reject invocations and records with fields that are not in the WIT, rather than
+    /// ignoring them (the default, so that peers built against newer versions of the WIT can still be served).
+    /// Optional fields (i.e. WIT `option<T>`s) are always defaulted to `None` when missing, for peers built against
+    /// older versions. Records with flattened fields always ignore unknown fields, as serde can't deny them
+    deny_unknown_fields => DenyUnknownFields(LitBool),
+
+    /// `wit_field_names: true` - (de)serialize the fields of records and invocations with their original WIT names
+    /// (ex. `content-type`), rather than the Rust ones (ex. `content_type`, the default), for peers on the lattice
+    /// that are not written in Rust. Note that this changes the wire format of map (named) encodings
+    wit_field_names => WitFieldNames(LitBool),
+}
+
+/// Arguments of `export_contract!`: the provider, optionally followed by the path to the SDK it was generated with
+/// (ex. `export_contract!(YourProvider, sdk_path: crate::deps::sdk)`)
+pub(crate) struct ExportContractArgs {
+    pub(crate) provider: Type,
+    pub(crate) sdk_path: Option<Path>,
+}
+
+impl Parse for ExportContractArgs {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let provider = input.parse::<Type>().map_err(|e| {
+            Error::new(
+                e.span(),
+                "expected a provider struct name, ex.: export_contract!(YourProvider);",
+            )
+        })?;
+        let mut sdk_path = None;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            input.parse::<kw::sdk_path>()?;
+            input.parse::<Token![:]>()?;
+            sdk_path = Some(crate_path(input.parse()?)?);
+            input.parse::<Option<Token![,]>>()?;
+        }
+        Ok(ExportContractArgs { provider, sdk_path })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::{quote, ToTokens};
+    use syn::parse_quote;
+
+    use super::*;
+
+    /// Extract options from braced wit-bindgen args
+    fn extract(args: TokenStream) -> Result<(ProviderOpts, TokenStream)> {
+        ProviderOpts::extract(quote!({ #args }))
+    }
+
+    /// Extract options from braced wit-bindgen args, expecting an error
+    fn extract_err(args: TokenStream) -> String {
+        match extract(args) {
+            Ok(_) => panic!("options were unexpectedly accepted"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn flatten_fields_are_extracted() {
+        let (opts, forwarded) = extract(quote!(
+            world: "settings",
+            flatten_fields: ["config.limits", "request-opts.extra-headers"],
+        ))
+        .expect("failed to extract options");
+        assert_eq!(
+            opts.flatten_fields,
+            HashSet::from([
+                ("Config".into(), "limits".into()),
+                ("RequestOpts".into(), "extra_headers".into()),
+            ])
+        );
+        assert_eq!(
+            forwarded.to_string(),
+            quote!({ world: "settings", }).to_string()
+        );
+    }
+
+    #[test]
+    fn flatten_fields_require_record_and_field() {
+        let err = extract_err(quote!(flatten_fields: ["limits"]));
+        assert!(err.contains("expected `<record>.<field>`"), "{err}");
+    }
+
+    #[test]
+    fn dispatch_fn_accepts_a_name() {
+        let (opts, _) =
+            extract(quote!(dispatch_fn: "handle_invocation")).expect("failed to extract options");
+        assert!(opts.dispatch_fn);
+        assert_eq!(
+            opts.dispatch_fn_name.expect("missing name"),
+            "handle_invocation"
+        );
+
+        let (opts, _) = extract(quote!(dispatch_fn: true)).expect("failed to extract options");
+        assert!(opts.dispatch_fn);
+        assert!(opts.dispatch_fn_name.is_none());
+    }
+
+    #[test]
+    fn dispatch_fn_rejects_invalid_names() {
+        let err = extract_err(quote!(dispatch_fn: "handle invocation"));
+        assert!(err.contains("invalid dispatch function name"), "{err}");
+
+        let err = extract_err(quote!(dispatch_fn: 1));
+        assert!(
+            err.contains("expected `true`, `false` or a function name"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn options_given_twice_are_rejected() {
+        let err = extract_err(quote!(mode: "sync", world: "settings", mode: "async"));
+        assert_eq!(err, "option `mode` given twice");
+    }
+
+    #[test]
+    fn unrecognized_choices_list_the_expected_ones() {
+        let err = extract_err(quote!(trait_style: "native"));
+        assert_eq!(
+            err,
+            "unrecognized trait style: `native`; expected `async_trait`, `trait_variant` or `boxed_future`"
+        );
+    }
+
+    #[test]
+    fn generic_type_options_may_contain_commas() {
+        let (opts, forwarded) = extract(quote!(
+            config: HashMap<String, String>,
+            host_error: Box<dyn Fn(u8, u8) -> Result<(), String>>,
+            world: "settings",
+        ))
+        .expect("failed to extract options");
+        let rendered = |ty: Type| ty.to_token_stream().to_string();
+        assert_eq!(
+            rendered(opts.config.expect("missing config")),
+            rendered(parse_quote!(HashMap<String, String>))
+        );
+        assert_eq!(
+            rendered(opts.host_error.expect("missing host error")),
+            rendered(parse_quote!(Box<dyn Fn(u8, u8) -> Result<(), String>>))
+        );
+        assert_eq!(
+            forwarded.to_string(),
+            quote!({ world: "settings", }).to_string()
+        );
+    }
+
+    #[test]
+    fn interface_features_may_be_qualified_by_package() {
+        let (opts, _) = extract(quote!(interface_features: {
+            "key-value": "kv",
+            "wasi-keyvalue/key-value": "wasi",
+        }))
+        .expect("failed to extract options");
+        assert_eq!(opts.interface_features["key_value"], "kv");
+        assert_eq!(opts.interface_features["wasi_keyvalue/key_value"], "wasi");
+    }
+
+    #[test]
+    fn wire_format_accepts_every_format() {
+        for (name, format) in [
+            ("sdk", WireFormat::Sdk),
+            ("msgpack", WireFormat::Msgpack),
+            ("json", WireFormat::Json),
+        ] {
+            let (opts, _) = extract(quote!(wire_format: #name)).expect("failed to extract options");
+            assert_eq!(opts.wire_format, format);
+        }
+
+        let err = extract_err(quote!(wire_format: "cbor"));
+        assert!(err.contains("unrecognized wire format: `cbor`"), "{err}");
+    }
+
+    #[test]
+    fn trait_suffix_must_be_a_non_empty_identifier() {
+        let (opts, _) =
+            extract(quote!(trait_suffix: "Provider")).expect("failed to extract options");
+        assert_eq!(opts.trait_suffix, "Provider");
+
+        for suffix in ["", "Key Value"] {
+            let err = extract_err(quote!(trait_suffix: #suffix));
+            assert!(err.contains("invalid trait suffix"), "{err}");
+        }
+    }
+
+    #[test]
+    fn export_contract_accepts_an_sdk_path() {
+        let args = syn::parse2::<ExportContractArgs>(quote!(KvProvider))
+            .expect("failed to parse provider");
+        assert!(args.sdk_path.is_none());
+
+        let args = syn::parse2::<ExportContractArgs>(quote!(KvProvider<B>, sdk_path: crate::sdk,))
+            .expect("failed to parse provider with sdk_path");
+        assert_eq!(
+            args.provider.to_token_stream().to_string(),
+            quote!(KvProvider<B>).to_string()
+        );
+        assert_eq!(
+            args.sdk_path.to_token_stream().to_string(),
+            quote!(crate::sdk).to_string()
+        );
+    }
+
+    #[test]
+    fn export_contract_rejects_relative_sdk_paths() {
+        let err = syn::parse2::<ExportContractArgs>(quote!(KvProvider, sdk_path: sdk))
+            .err()
+            .expect("relative sdk_path was accepted")
+            .to_string();
+        assert!(err.contains("crate paths must be absolute"), "{err}");
+    }
+}
@@ -0,0 +1,1422 @@
+//! Tests over the code that [`expand`] generates for WIT fixtures (see `tests/fixtures`)
+
+use std::path::PathBuf;
+
+use quote::quote;
+
+use super::*;
+
+/// Environment variable that causes snapshot tests to (re)write their snapshots rather than check them
+const UPDATE_SNAPSHOTS_ENV_VAR: &str = "UPDATE_SNAPSHOTS";
+
+/// Expand `generate!` input, parsing the generated code
+fn expand_file(input: proc_macro2::TokenStream) -> syn::File {
+    let expanded = expand(input);
+    syn::parse2(expanded.clone())
+        .unwrap_or_else(|e| panic!("failed to parse generated code ({e}):\n{expanded}"))
+}
+
+/// Expand `generate!` input, expecting it to produce a compile error, whose message is returned
+fn expand_err(input: proc_macro2::TokenStream) -> String {
+    let expanded = expand(input);
+    let file = syn::parse2::<syn::File>(expanded.clone())
+        .unwrap_or_else(|e| panic!("failed to parse generated code ({e}):\n{expanded}"));
+    let errors = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Macro(m)
+                if m.mac
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|s| s.ident == "compile_error") =>
+            {
+                Some(
+                    m.mac
+                        .parse_body::<LitStr>()
+                        .expect("invalid compile_error!")
+                        .value(),
+                )
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        !errors.is_empty(),
+        "expected a compile error, generated:\n{expanded}"
+    );
+    errors.join("\n")
+}
+
+/// Check the rendered expansion of `generate!` input against a snapshot in `tests/snapshots`
+fn assert_snapshot(name: &str, input: proc_macro2::TokenStream) {
+    let rendered = render_expansion(&expand(input));
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.snap"));
+    if std::env::var(UPDATE_SNAPSHOTS_ENV_VAR).is_ok_and(|v| !v.is_empty()) {
+        std::fs::write(&path, rendered).expect("failed to write snapshot");
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "failed to read snapshot [{}] ({e}), run with {UPDATE_SNAPSHOTS_ENV_VAR}=1 to create it",
+            path.display()
+        )
+    });
+    if rendered != expected {
+        let diff = expected
+            .lines()
+            .zip(rendered.lines())
+            .enumerate()
+            .find(|(_, (expected, rendered))| expected != rendered)
+            .map(|(idx, (expected, rendered))| {
+                format!("line {}:\n- {expected}\n+ {rendered}", idx + 1)
+            })
+            .unwrap_or_else(|| "(output length differs)".into());
+        panic!(
+            "generated code differs from snapshot [{}], run with {UPDATE_SNAPSHOTS_ENV_VAR}=1 if the change was intended\n{diff}",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn keyvalue_snapshot() {
+    assert_snapshot(
+        "keyvalue",
+        quote!(KvProvider, {
+            world: "keyvalue",
+            path: "tests/fixtures/keyvalue",
+        }),
+    );
+}
+
+#[test]
+fn render_without_formatting_puts_items_on_lines() {
+    let file: syn::File = syn::parse_quote! {
+        #![allow(unused)]
+        /// Outer
+        pub mod outer {
+            #![allow(dead_code)]
+            struct Inner;
+        }
+        fn f() {}
+    };
+    let mut rendered = String::new();
+    render_items(&file.attrs, &file.items, 0, &mut rendered);
+    assert_eq!(
+        rendered,
+        [
+            "# ! [allow (unused)]",
+            "# [doc = r\" Outer\"]",
+            "pub mod outer {",
+            "    # ! [allow (dead_code)]",
+            "    struct Inner ;",
+            "}",
+            "fn f () { }",
+            "",
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn double_comma_after_provider_is_rejected() {
+    let err = expand_err(quote!(KvProvider, , {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+    }));
+    assert!(
+        err.contains("expected wit-bindgen args after the provider struct name"),
+        "{err}"
+    );
+}
+
+#[test]
+fn double_comma_in_args_is_rejected() {
+    let err = expand_err(quote!(KvProvider, "keyvalue" in "tests/fixtures/keyvalue",,));
+    assert!(err.contains("must not contain consecutive commas"), "{err}");
+}
+
+#[test]
+fn single_trailing_comma_is_allowed() {
+    expand_file(quote!(KvProvider, "keyvalue" in "tests/fixtures/keyvalue",));
+}
+
+#[test]
+fn trailing_comma_after_args_is_ignored() {
+    assert_eq!(
+        expand(quote!(KvProvider, "keyvalue" in "tests/fixtures/keyvalue",)).to_string(),
+        expand(quote!(KvProvider, "keyvalue" in "tests/fixtures/keyvalue")).to_string(),
+    );
+    assert_eq!(
+        expand(quote!(KvProvider, {
+            world: "keyvalue",
+            path: "tests/fixtures/keyvalue",
+        },))
+        .to_string(),
+        expand(quote!(KvProvider, {
+            world: "keyvalue",
+            path: "tests/fixtures/keyvalue",
+        }))
+        .to_string(),
+    );
+}
+
+#[test]
+fn empty_bindgen_output_is_detected() {
+    let empty: syn::File = syn::parse_quote!();
+    assert!(is_empty_bindgen_output(&empty.items));
+
+    let only_consts: syn::File = syn::parse_quote! {
+        const _: &str = include_str!("wit/keyvalue.wit");
+    };
+    assert!(is_empty_bindgen_output(&only_consts.items));
+
+    let bindings = expand_file(quote!(KvProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+    }));
+    assert!(!is_empty_bindgen_output(&bindings.items));
+}
+
+/// Find a module generated at the top level of the expansion
+fn find_mod<'a>(file: &'a syn::File, name: &str) -> &'a ItemMod {
+    file.items
+        .iter()
+        .find_map(|item| match item {
+            Item::Mod(m) if m.ident == name => Some(m),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("module [{name}] was not generated"))
+}
+
+#[test]
+fn bench_harness_is_gated_on_bench_feature_by_default() {
+    let file = expand_file(quote!(KvProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+        bench_harness: true,
+    }));
+    let bench = find_mod(&file, "dispatch_bench");
+    let cfg: syn::Attribute = syn::parse_quote!(#[cfg(feature = "bench")]);
+    assert!(bench.attrs.contains(&cfg));
+}
+
+#[test]
+fn legacy_default_world_is_reported() {
+    let err = expand_err(quote!(KvProvider, {
+        inline: "
+            package wasmcloud:keyvalue
+
+            interface store {
+                count: func() -> u64
+            }
+
+            default world keyvalue {
+                import store
+            }
+        ",
+    }));
+    assert_eq!(err, LEGACY_DEFAULT_WORLD_ERROR_TEXT);
+}
+
+/// Types generated by wit-bindgen for a `wasmcloud:messaging/types` interface, by [`path_key`]
+fn messaging_type_paths() -> HashMap<String, Punctuated<PathSegment, PathSep>> {
+    HashMap::from([(
+        "wasmcloud::messaging::types::BrokerMessage".to_string(),
+        syn::parse_quote!(wasmcloud::messaging::types::BrokerMessage),
+    )])
+}
+
+/// Build the lattice methods of functions imported from a `wasmcloud:messaging/consumer` interface,
+/// which may refer to the types of [`messaging_type_paths`]
+fn messaging_methods(funcs: Vec<syn::ItemFn>) -> syn::Result<Vec<LatticeMethod>> {
+    let iface_path = WitInterfacePath {
+        ns: "wasmcloud".into(),
+        package: "messaging".into(),
+        version: None,
+        iface: "consumer".into(),
+    };
+    let mut methods = build_lattice_methods_by_wit_interface(
+        &messaging_type_paths(),
+        &HashMap::new(),
+        &HashMap::from([(iface_path, funcs)]),
+        &HashMap::new(),
+        &ProviderOpts::default(),
+        false,
+    )?;
+    Ok(methods.remove("Consumer").unwrap_or_default())
+}
+
+/// Convert an argument type to the owned form used by invocation structs, given the
+/// types generated by wit-bindgen for a `wasmcloud:messaging/types` interface
+fn owned(ty: proc_macro2::TokenStream) -> syn::Type {
+    let paths = messaging_type_paths();
+    let owned_forms = HashMap::new();
+    let lookup = TypeLookup {
+        paths: &paths,
+        scope: None,
+        owned_forms: &owned_forms,
+    };
+    let ty = ty.into_iter().collect::<Vec<TokenTree>>();
+    syn::parse2(owned_type(&ty, lookup, &syn::parse_quote!(String))).expect("invalid owned type")
+}
+
+#[test]
+fn bare_slices_are_converted_to_vecs() {
+    assert_eq!(owned(quote!(&[u8])), syn::parse_quote!(Vec<u8>));
+    assert_eq!(owned(quote!(&'a [u8])), syn::parse_quote!(Vec<u8>));
+    assert_eq!(owned(quote!(&[u32])), syn::parse_quote!(Vec<u32>));
+}
+
+#[test]
+fn mut_references_are_converted_to_owned_types() {
+    assert_eq!(owned(quote!(&mut str)), syn::parse_quote!(String));
+    assert_eq!(owned(quote!(&'a mut str)), syn::parse_quote!(String));
+    assert_eq!(owned(quote!(&mut [u8])), syn::parse_quote!(Vec<u8>));
+    assert_eq!(
+        owned(quote!(&mut BrokerMessage)),
+        syn::parse_quote!(wasmcloud::messaging::types::BrokerMessage)
+    );
+}
+
+#[test]
+fn mut_reference_arguments_become_owned_members() {
+    let methods = messaging_methods(vec![syn::parse_quote! {
+        pub fn update(msg: &mut BrokerMessage, subject: &mut str) {}
+    }])
+    .unwrap_or_else(|e| panic!("failed to build methods: {e}"));
+    assert_eq!(
+        methods[0].struct_members.to_string(),
+        quote!(
+            msg: wasmcloud::messaging::types::BrokerMessage,
+            subject: String
+        )
+        .to_string()
+    );
+}
+
+#[test]
+fn tuple_elements_are_converted_to_owned_types() {
+    assert_eq!(
+        owned(quote!((u32, &str))),
+        syn::parse_quote!((u32, String,))
+    );
+    assert_eq!(
+        owned(quote!((&[u8], Option<&BrokerMessage>))),
+        syn::parse_quote!((Vec<u8>, Option<wasmcloud::messaging::types::BrokerMessage>,))
+    );
+}
+
+#[test]
+fn tuple_arguments_become_single_owned_members() {
+    let methods = messaging_methods(vec![syn::parse_quote! {
+        pub fn swap(pair: (u32, &str)) -> (wit_bindgen::rt::string::String, u32) {}
+    }])
+    .unwrap_or_else(|e| panic!("failed to build methods: {e}"));
+    assert_eq!(methods[0].invocation_args, [format_ident!("pair")]);
+    assert_eq!(
+        methods[0].struct_members.to_string(),
+        quote!(pair: (u32, String,)).to_string()
+    );
+}
+
+/// Find a struct generated at the top level of the expansion
+fn find_struct<'a>(file: &'a syn::File, name: &str) -> &'a syn::ItemStruct {
+    file.items
+        .iter()
+        .find_map(|item| match item {
+            Item::Struct(s) if s.ident == name => Some(s),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("struct [{name}] was not generated"))
+}
+
+/// Find a struct generated in a (nested) module of the expansion (ex. by wit-bindgen for a WIT record)
+fn find_module_struct<'a>(
+    file: &'a syn::File,
+    modules: &[&str],
+    name: &str,
+) -> &'a syn::ItemStruct {
+    let mut items = &file.items;
+    for module in modules {
+        items = items
+            .iter()
+            .find_map(|item| match item {
+                Item::Mod(ItemMod {
+                    ident,
+                    content: Some((_, items)),
+                    ..
+                }) if ident == module => Some(items),
+                _ => None,
+            })
+            .unwrap_or_else(|| panic!("module [{module}] was not generated"));
+    }
+    items
+        .iter()
+        .find_map(|item| match item {
+            Item::Struct(s) if s.ident == name => Some(s),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("struct [{name}] was not generated"))
+}
+
+/// Get the traits derived by a struct
+fn derives(s: &syn::ItemStruct) -> Vec<String> {
+    s.attrs
+        .iter()
+        .filter(|a| a.path().is_ident("derive"))
+        .flat_map(|a| {
+            a.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)
+                .expect("invalid derive")
+        })
+        .map(|p| p.to_token_stream().to_string().replace(' ', ""))
+        .collect()
+}
+
+#[test]
+fn invocation_structs_derive_default_only_when_members_do() {
+    let file = expand_file(quote!(KvProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+    }));
+    assert!(derives(find_struct(&file, "KeyvalueStoreGetInvocation")).contains(&"Default".into()));
+    // The metadata record doesn't implement `Default`
+    assert!(!derives(find_struct(&file, "KeyvalueStoreSetInvocation")).contains(&"Default".into()));
+}
+
+#[test]
+fn owning_ownership_is_detected() {
+    assert!(is_owning_ownership(
+        &quote!({ world: "keyvalue", ownership: Owning })
+    ));
+    assert!(!is_owning_ownership(
+        &quote!({ world: "keyvalue", ownership: Borrowing { duplicate_if_necessary: true } })
+    ));
+    assert!(!is_owning_ownership(&quote!({ world: "keyvalue" })));
+}
+
+#[test]
+fn ownership_modes_are_respected() {
+    let members = |ownership: proc_macro2::TokenStream| {
+        let file = expand_file(quote!(KvProvider, {
+            world: "keyvalue",
+            path: "tests/fixtures/keyvalue",
+            ownership: #ownership,
+        }));
+        find_struct(&file, "KeyvalueStoreSetInvocation")
+            .fields
+            .iter()
+            .map(|f| f.ty.to_token_stream().to_string())
+            .collect::<Vec<_>>()
+    };
+    // Records are generated without lifetimes, so are used as-is
+    assert_eq!(
+        members(quote!(Owning)),
+        [
+            quote!(String),
+            quote!(String),
+            quote!(wasmcloud::keyvalue::store::Metadata)
+        ]
+        .map(|t| t.to_string())
+    );
+
+    // Records that are only borrowed have no owned form to receive
+    let err = expand_err(quote!(KvProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+        ownership: Borrowing { duplicate_if_necessary: true },
+    }));
+    assert!(err.contains("of WIT function `set`"), "{err}");
+    assert!(err.contains("`ownership: Owning`"), "{err}");
+}
+
+#[test]
+fn lists_of_references_are_converted_element_wise() {
+    assert_eq!(owned(quote!(Vec<&str>)), syn::parse_quote!(Vec<String>));
+    assert_eq!(
+        owned(quote!(Vec<&BrokerMessage>)),
+        syn::parse_quote!(Vec<wasmcloud::messaging::types::BrokerMessage>)
+    );
+    assert_eq!(owned(quote!(&[&str])), syn::parse_quote!(Vec<String>));
+    assert_eq!(
+        owned(quote!(&[BrokerMessage])),
+        syn::parse_quote!(Vec<wasmcloud::messaging::types::BrokerMessage>)
+    );
+}
+
+#[test]
+fn references_in_qualified_wrappers_are_converted() {
+    assert_eq!(
+        owned(quote!(wit_bindgen::rt::vec::Vec<&str>)),
+        syn::parse_quote!(wit_bindgen::rt::vec::Vec<String>)
+    );
+    assert_eq!(
+        owned(quote!(::std::option::Option<&BrokerMessage>)),
+        syn::parse_quote!(::std::option::Option<wasmcloud::messaging::types::BrokerMessage>)
+    );
+
+    let methods = messaging_methods(vec![syn::parse_quote! {
+        pub fn delete_many(keys: wit_bindgen::rt::vec::Vec<&str>) -> u32 {}
+    }])
+    .unwrap_or_else(|e| panic!("failed to build methods: {e}"));
+    assert_eq!(
+        methods[0].struct_members.to_string(),
+        quote!(keys: wit_bindgen::rt::vec::Vec<String>).to_string()
+    );
+}
+
+#[test]
+fn list_arguments_become_owned_members() {
+    let methods = messaging_methods(vec![
+        syn::parse_quote! {
+            pub fn delete_many(keys: Vec<&str>) -> u32 {}
+        },
+        syn::parse_quote! {
+            pub fn publish_batch(msgs: &[&BrokerMessage]) -> u32 {}
+        },
+    ])
+    .unwrap_or_else(|e| panic!("failed to build methods: {e}"));
+    assert_eq!(
+        methods[0].struct_members.to_string(),
+        quote!(keys: Vec<String>).to_string()
+    );
+    assert_eq!(
+        methods[1].struct_members.to_string(),
+        quote!(msgs: Vec<wasmcloud::messaging::types::BrokerMessage>).to_string()
+    );
+}
+
+#[test]
+fn dispatch_fn_is_named_after_provider_unless_named() {
+    let is_fn = |file: &syn::File, name: &str| {
+        file.items
+            .iter()
+            .any(|item| matches!(item, Item::Fn(f) if f.sig.ident == name))
+    };
+    let file = expand_file(quote!(crate::providers::KvProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+        dispatch_fn: true,
+    }));
+    assert!(is_fn(&file, "handle_kv_provider"));
+
+    let file = expand_file(quote!(KvProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+        dispatch_fn: "handle_invocation",
+    }));
+    assert!(is_fn(&file, "handle_invocation"));
+}
+
+#[test]
+fn operations_are_only_generated_with_dispatch() {
+    let has_operation = |file: &syn::File| {
+        file.items
+            .iter()
+            .any(|item| matches!(item, Item::Enum(e) if e.ident == "Operation"))
+    };
+    let file = expand_file(quote!(KvProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+    }));
+    assert!(has_operation(&file));
+
+    let file = expand_file(quote!(KvProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+        dispatch: false,
+    }));
+    assert!(!has_operation(&file));
+}
+
+#[test]
+fn where_clauses_are_kept_on_generated_impls() {
+    let file = expand_file(quote!(KvProvider<A, B> where A: Clone, B: Default + Send, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+    }));
+    let expected = quote!(where A: Clone, B: Default + Send).to_string();
+    let provider: syn::Type = syn::parse_quote!(KvProvider<A, B>);
+    let impls = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Impl(i) if *i.self_ty == provider => Some(i),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert!(!impls.is_empty());
+    for i in impls {
+        assert_eq!(
+            i.generics.where_clause.to_token_stream().to_string(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn providers_sharing_the_name_of_a_generated_type_are_reported() {
+    let err = expand_err(quote!(KeyvalueStoreGetInvocation, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+    }));
+    assert!(
+        err.contains(
+            "provider `KeyvalueStoreGetInvocation` has the same name as the generated invocation struct"
+        ),
+        "{err}"
+    );
+
+    let err = expand_err(quote!(Store, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+    }));
+    assert!(
+        err.contains("provider `Store` has the same name as the generated trait `Store`"),
+        "{err}"
+    );
+
+    // Every generated item is checked, including those only generated with some options
+    for (provider, opts, expected) in [
+        (quote!(wasmcloud), quote!(), "item `wasmcloud`"),
+        (
+            quote!(LocalStore),
+            quote!(trait_style: "trait_variant",),
+            "trait `LocalStore`",
+        ),
+        (
+            quote!(StoreHostClient),
+            quote!(host_error: String,),
+            "type `StoreHostClient`",
+        ),
+        (
+            quote!(PROVIDER_CONTRACT),
+            quote!(contract_metadata: true,),
+            "constant `PROVIDER_CONTRACT`",
+        ),
+        (
+            quote!(provider_method_checks),
+            quote!(),
+            "module `provider_method_checks`",
+        ),
+        (
+            quote!(method_concurrency),
+            quote!(method_concurrency: { "get": 1 },),
+            "module `method_concurrency`",
+        ),
+    ] {
+        let err = expand_err(quote!(#provider, {
+            world: "keyvalue",
+            path: "tests/fixtures/keyvalue",
+            #opts
+        }));
+        assert!(
+            err.contains(&format!("has the same name as the generated {expected}")),
+            "{err}"
+        );
+    }
+
+    // Providers declared in another module don't conflict
+    expand_file(quote!(providers::KeyvalueStoreGetInvocation, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+    }));
+}
+
+#[test]
+fn provider_methods_sharing_the_name_of_a_generated_associated_item_are_reported() {
+    let wit = "
+        package wasmcloud:lifecycle
+
+        interface runner {
+            start: func() -> bool
+        }
+
+        world lifecycle {
+            import runner
+        }
+    ";
+    let err = expand_err(quote!(providers::Runner, { inline: #wit, entrypoint: true }));
+    assert!(
+        err.contains(
+            "provider method `start` has the same name as the function `start` generated on the provider"
+        ),
+        "{err}"
+    );
+
+    // The method only clashes with the helper generated by `entrypoint`
+    expand_file(quote!(providers::Runner, { inline: #wit }));
+}
+
+#[test]
+fn dispatch_arms_of_gated_interfaces_are_gated() {
+    let file = expand_file(quote!(KvProvider, {
+        inline: "
+            package wasmcloud:keyvalue
+
+            interface store {
+                get: func(key: string) -> option<string>
+            }
+
+            interface cache {
+                evict: func(key: string) -> bool
+            }
+
+            world keyvalue {
+                import store
+                import cache
+            }
+        ",
+        interface_features: { "cache": "cache" },
+    }));
+    let dispatch = dispatch_impl(&file);
+    let gated = quote!(#[cfg(feature = "cache")]).to_string();
+    assert!(
+        dispatch.contains(&format!("{gated} Operation :: Evict =>")),
+        "{dispatch}"
+    );
+    assert!(dispatch.contains("Operation :: Get =>"), "{dispatch}");
+    assert!(
+        !dispatch.contains(&format!("{gated} Operation :: Get =>")),
+        "{dispatch}"
+    );
+}
+
+/// Render the MessageDispatch impl of the generated code
+fn dispatch_impl(file: &syn::File) -> String {
+    file.items
+        .iter()
+        .find_map(|item| match item {
+            Item::Impl(i)
+                if i.trait_.as_ref().is_some_and(|(_, p, _)| {
+                    p.segments
+                        .last()
+                        .is_some_and(|s| s.ident == "MessageDispatch")
+                }) =>
+            {
+                Some(i.to_token_stream().to_string())
+            }
+            _ => None,
+        })
+        .expect("missing MessageDispatch impl")
+}
+
+#[test]
+fn interfaces_sharing_a_name_are_gated_by_name_or_package() {
+    let gated = quote!(#[cfg(feature = "replica")]).to_string();
+
+    // Every interface with the name is gated
+    let dispatch = dispatch_impl(&expand_file(quote!(KvProvider, {
+        world: "replica",
+        path: "tests/fixtures/replica",
+        interface_features: { "store": "replica" },
+    })));
+    assert!(
+        dispatch.contains(&format!("{gated} Operation :: Fetch =>")),
+        "{dispatch}"
+    );
+    assert!(
+        dispatch.contains(&format!("{gated} Operation :: Get =>")),
+        "{dispatch}"
+    );
+
+    // Only the interface of the given package is gated
+    let dispatch = dispatch_impl(&expand_file(quote!(KvProvider, {
+        world: "replica",
+        path: "tests/fixtures/replica",
+        interface_features: { "replica/store": "replica" },
+    })));
+    assert!(
+        dispatch.contains(&format!("{gated} Operation :: Fetch =>")),
+        "{dispatch}"
+    );
+    assert!(
+        !dispatch.contains(&format!("{gated} Operation :: Get =>")),
+        "{dispatch}"
+    );
+}
+
+#[test]
+fn unknown_interface_features_are_reported() {
+    let err = expand_err(quote!(KvProvider, {
+        world: "replica",
+        path: "tests/fixtures/replica",
+        interface_features: { "stores": "replica", "keyvalue/fetch": "fetch" },
+    }));
+    assert!(
+        err.contains("unknown interfaces [keyvalue/fetch, stores] in interface_features; expected one of [store]"),
+        "{err}"
+    );
+}
+
+#[test]
+fn functions_mapping_to_the_same_method_are_reported() {
+    let err = expand_err(quote!(KvProvider, {
+        inline: "
+            package wasmcloud:keyvalue
+
+            interface store {
+                get-a: func(key: string) -> option<string>
+                get-A: func(key: string) -> option<string>
+            }
+
+            world keyvalue {
+                import store
+            }
+        ",
+    }));
+    assert!(
+        err.contains(
+            "WIT functions `get-a` and `get-A` of interface `store` both map to method `get_a`"
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+fn expected_signatures_use_the_sdk_path() {
+    let file = expand_file(quote!(KvProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+        sdk_path: crate::sdk,
+        method_skeleton: true,
+    }));
+    let skeleton = file
+        .items
+        .iter()
+        .find_map(|item| match item {
+            Item::Const(c) if c.ident == "PROVIDER_METHOD_SKELETON" => match &*c.expr {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) => Some(s.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .expect("missing method skeleton");
+    assert!(
+        skeleton.contains("ld: &crate::sdk::core::LinkDefinition) -> bool"),
+        "{skeleton}"
+    );
+    assert!(
+        skeleton.contains("async fn count(&self, ctx: crate::sdk::Context) -> u64"),
+        "{skeleton}"
+    );
+    assert!(!skeleton.contains("wasmcloud_provider_sdk"), "{skeleton}");
+
+    let Some((_, checks)) = &find_mod(&file, "provider_method_checks").content else {
+        panic!("method checks are empty");
+    };
+    let expected = checks
+        .iter()
+        .filter_map(|item| match item {
+            Item::Fn(f) => Some(doc_lines(&f.attrs).join("")),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        expected.contains(
+            &"Expected provider method: `async fn count(&self, ctx: crate::sdk::Context) -> u64`"
+                .to_string()
+        ),
+        "{expected:?}"
+    );
+}
+
+#[test]
+fn generic_provider_checks_shadow_local_trait_variants() {
+    let file = expand_file(quote!(KvProvider<B> where B: Backend, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+        trait_style: "trait_variant",
+    }));
+    let Some((_, checks)) = &find_mod(&file, "provider_method_checks").content else {
+        panic!("method checks are empty");
+    };
+    let shadowed = checks
+        .iter()
+        .filter_map(|item| match item {
+            Item::Struct(s) => Some(s.ident.to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(shadowed, ["Store", "LocalStore"]);
+}
+
+#[test]
+fn recursive_records_are_reported() {
+    let err = expand_err(quote!(TreeProvider, {
+        inline: "
+            package wasmcloud:tree
+
+            interface nodes {
+                record node {
+                    name: string,
+                    children: list<node>,
+                }
+
+                walk: func(root: node) -> u32
+            }
+
+            world tree {
+                import nodes
+            }
+        ",
+    }));
+    assert!(err.starts_with("type `node` depends on itself"), "{err}");
+    assert!(err.ends_with(RECURSIVE_TYPE_ERROR_TEXT), "{err}");
+    assert!(!err.contains("backtrace"), "{err}");
+}
+
+#[test]
+fn boxed_self_references_are_qualified() {
+    assert_eq!(
+        owned(quote!(Option<Box<BrokerMessage>>)),
+        syn::parse_quote!(Option<Box<wasmcloud::messaging::types::BrokerMessage>>)
+    );
+}
+
+/// WIT of a keyvalue package at the given version (ex. `@0.2.0`, or an empty string for no version)
+fn keyvalue_wit(version: &str) -> String {
+    format!(
+        "
+        package wasmcloud:keyvalue{version}
+
+        interface store {{
+            get: func(key: string) -> option<string>
+        }}
+
+        world keyvalue {{
+            import store
+        }}
+        "
+    )
+}
+
+/// Get the lattice method names that are dispatched
+fn lattice_method_names(file: &syn::File) -> Vec<String> {
+    file.items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Impl(i) => Some(i.to_token_stream().to_string()),
+            _ => None,
+        })
+        .flat_map(|i| {
+            i.split('"')
+                .skip(1)
+                .step_by(2)
+                .map(String::from)
+                .collect::<Vec<_>>()
+        })
+        .filter(|s| s.contains(".get") || s == "Message.Get")
+        .collect()
+}
+
+/// Get the names of the invocation structs that are generated
+fn invocation_struct_names(file: &syn::File) -> Vec<String> {
+    file.items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Struct(s) if s.ident.to_string().ends_with("Invocation") => {
+                Some(s.ident.to_string())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn unversioned_package_names() {
+    let wit = keyvalue_wit("");
+    let file = expand_file(
+        quote!(KvProvider, { inline: #wit, lattice_method_style: "wit", versioned_struct_names: true }),
+    );
+    assert!(lattice_method_names(&file)
+        .iter()
+        .all(|m| m == "wasmcloud:keyvalue/store.get"));
+    assert_eq!(
+        invocation_struct_names(&file),
+        vec!["KeyvalueStoreGetInvocation"]
+    );
+}
+
+#[test]
+fn versioned_package_names() {
+    let wit = keyvalue_wit("@0.2.0");
+    let file = expand_file(
+        quote!(KvProvider, { inline: #wit, lattice_method_style: "wit", versioned_struct_names: true }),
+    );
+    let methods = lattice_method_names(&file);
+    assert!(!methods.is_empty());
+    assert!(methods
+        .iter()
+        .all(|m| m == "wasmcloud:keyvalue/store@0.2.0.get"));
+    assert_eq!(
+        invocation_struct_names(&file),
+        vec!["KeyvalueV020StoreGetInvocation"]
+    );
+}
+
+#[test]
+fn versioned_package_unversioned_struct_names() {
+    let wit = keyvalue_wit("@0.2.0");
+    let file = expand_file(quote!(KvProvider, { inline: #wit, lattice_method_style: "wit" }));
+    assert_eq!(
+        invocation_struct_names(&file),
+        vec!["KeyvalueStoreGetInvocation"]
+    );
+}
+
+#[test]
+fn method_names_shared_across_interfaces_are_reported() {
+    let err = expand_err(quote!(KvProvider, {
+        inline: "
+            package wasmcloud:keyvalue
+
+            interface store {
+                get: func(key: string) -> option<string>
+                count: func() -> u64
+            }
+
+            interface cache {
+                get: func(key: string) -> option<list<u8>>
+                evict: func(key: string)
+            }
+
+            world keyvalue {
+                import store
+                import cache
+            }
+        ",
+    }));
+    assert!(
+        err.starts_with("multiple WIT interfaces define methods with the same name"),
+        "{err}"
+    );
+    assert!(err.contains("`get` (in `Cache`, `Store`)"), "{err}");
+    assert!(
+        !err.contains("`count`") && !err.contains("`evict`"),
+        "{err}"
+    );
+}
+
+#[test]
+fn resources_are_reported() {
+    let err = expand_err(quote!(BlobProvider, {
+        inline: "
+            package wasmcloud:blobstore
+
+            interface container {
+                resource bucket {
+                    open: static func(name: string) -> bucket
+                }
+
+                exists: func(name: string) -> bool
+            }
+
+            world blobstore {
+                import container
+            }
+        ",
+    }));
+    assert!(
+        err.starts_with("WIT resource `wasmcloud:blobstore/container.bucket` is not supported"),
+        "{err}"
+    );
+}
+
+#[test]
+fn records_containing_error_enums_derive_serde() {
+    // wit-bindgen generates inherent impls (with `name()` and `message()`) for error enums,
+    // which must not be mistaken for resource handles
+    let file = expand_file(quote!(KvProvider, {
+        inline: "
+            package wasmcloud:keyvalue
+
+            interface store {
+                enum error-code {
+                    not-found,
+                    denied,
+                }
+
+                record outcome {
+                    key: string,
+                    code: option<error-code>,
+                }
+
+                get: func(key: string) -> result<string, error-code>
+                report: func(outcome: outcome) -> u32
+            }
+
+            world keyvalue {
+                import store
+            }
+        ",
+    }));
+    let outcome = find_module_struct(&file, &["wasmcloud", "keyvalue", "store"], "Outcome");
+    let derives = derives(outcome);
+    assert!(
+        derives.iter().any(|d| d.ends_with("Serialize")),
+        "{derives:?}"
+    );
+    assert!(
+        derives.iter().any(|d| d.ends_with("Deserialize")),
+        "{derives:?}"
+    );
+}
+
+#[test]
+fn same_named_records_are_resolved_per_interface() {
+    let file = expand_file(quote!(KvProvider, {
+        inline: "
+            package wasmcloud:keyvalue
+
+            interface store {
+                record metadata {
+                    content-type: string,
+                }
+
+                set: func(key: string, metadata: metadata)
+            }
+
+            interface blobs {
+                record metadata {
+                    size: u64,
+                }
+
+                put: func(name: string, metadata: metadata)
+            }
+
+            world keyvalue {
+                import store
+                import blobs
+            }
+        ",
+    }));
+    let member_type = |struct_name: &str| {
+        find_struct(&file, struct_name)
+            .fields
+            .iter()
+            .find(|f| f.ident.as_ref().is_some_and(|i| i == "metadata"))
+            .map(|f| f.ty.to_token_stream().to_string().replace(' ', ""))
+            .expect("missing metadata member")
+    };
+    assert_eq!(
+        member_type("KeyvalueStoreSetInvocation"),
+        "wasmcloud::keyvalue::store::Metadata"
+    );
+    assert_eq!(
+        member_type("KeyvalueBlobsPutInvocation"),
+        "wasmcloud::keyvalue::blobs::Metadata"
+    );
+}
+
+#[test]
+fn key_value_lists_of_references_become_owned_pairs() {
+    assert_eq!(
+        owned(quote!(&[(&str, &str)])),
+        syn::parse_quote!(Vec<(String, String,)>)
+    );
+}
+
+#[test]
+fn key_value_lists_with_hashable_keys_become_maps() {
+    let string_type: syn::Type = syn::parse_quote!(String);
+    let map = |ty: proc_macro2::TokenStream| {
+        map_list_type(&ty, &string_type).map(|ty| ty.to_string().replace(' ', ""))
+    };
+    assert_eq!(
+        map(quote!(Vec<(String, String)>)).as_deref(),
+        Some("::std::collections::HashMap<String,String>")
+    );
+    assert_eq!(
+        map(quote!(Vec<(wit_bindgen::rt::string::String, u32)>)).as_deref(),
+        Some("::std::collections::HashMap<wit_bindgen::rt::string::String,u32>")
+    );
+    assert_eq!(map(quote!(Vec<(f32, u32)>)), None);
+    assert_eq!(map(quote!(Vec<(Vec<u8>, u32)>)), None);
+    assert_eq!(map(quote!(Vec<(String, u32, u32)>)), None);
+}
+
+/// Input for expanding the large `catalog` fixture
+fn catalog_input() -> proc_macro2::TokenStream {
+    quote!(CatalogProvider, {
+        world: "catalog",
+        path: "tests/fixtures/catalog",
+    })
+}
+
+#[test]
+fn large_worlds_expand() {
+    let file = expand_file(catalog_input());
+    assert_eq!(invocation_struct_names(&file).len(), 120);
+
+    // Both the items of wit-bindgen and those added to them have lints allowed
+    let allow: Attribute = syn::parse_quote!(#[allow(unused, clippy::all)]);
+    for name in ["wasmcloud", "Operation"] {
+        let attrs = file
+            .items
+            .iter()
+            .find_map(|item| match item {
+                Item::Mod(m) if m.ident == name => Some(&m.attrs),
+                Item::Enum(e) if e.ident == name => Some(&e.attrs),
+                _ => None,
+            })
+            .unwrap_or_else(|| panic!("missing {name}"));
+        assert!(attrs.contains(&allow), "{name} does not allow lints");
+    }
+}
+
+/// Measures how long expanding a large world takes
+/// (run with `cargo test --lib expansion_time -- --ignored --nocapture`)
+#[test]
+#[ignore = "benchmark"]
+fn expansion_time() {
+    const RUNS: u32 = 10;
+    let start = std::time::Instant::now();
+    for _ in 0..RUNS {
+        expand(catalog_input());
+    }
+    println!(
+        "expanded the catalog fixture in {:?} on average",
+        start.elapsed() / RUNS
+    );
+}
+
+/// Get the doc comments of an item's attributes
+fn doc_lines(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter_map(|a| match &a.meta {
+            Meta::NameValue(nv) if nv.path.is_ident("doc") => match &nv.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) => Some(s.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn wit_docs_are_kept_on_interface_methods() {
+    let file = expand_file(quote!(KvProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+    }));
+    let expected = vec!["Get the value of a key, if it is set".to_string()];
+
+    let trait_fn = file
+        .items
+        .iter()
+        .find_map(|item| match item {
+            Item::Trait(t) if t.ident == "Store" => t.items.iter().find_map(|i| match i {
+                syn::TraitItem::Fn(f) if f.sig.ident == "get" => Some(f),
+                _ => None,
+            }),
+            _ => None,
+        })
+        .expect("missing get method of the Store trait");
+    assert_eq!(doc_lines(&trait_fn.attrs), expected);
+
+    let impl_fn = file
+        .items
+        .iter()
+        .find_map(|item| match item {
+            Item::Impl(i)
+                if i.trait_
+                    .as_ref()
+                    .is_some_and(|(_, p, _)| p.is_ident("Store")) =>
+            {
+                i.items.iter().find_map(|i| match i {
+                    syn::ImplItem::Fn(f) if f.sig.ident == "get" => Some(f),
+                    _ => None,
+                })
+            }
+            _ => None,
+        })
+        .expect("missing get method of the Store impl");
+    assert_eq!(doc_lines(&impl_fn.attrs), expected);
+}
+
+/// Assert that every generated (top-level) item allows lints, naming the ones that don't
+fn assert_items_allow_lints(file: &mut syn::File) {
+    let allow: Attribute = syn::parse_quote!(#[allow(unused, clippy::all)]);
+    let missing = file
+        .items
+        .iter_mut()
+        .filter_map(|item| {
+            let name = item.to_token_stream().to_string();
+            item_attrs_mut(item)
+                .filter(|attrs| !attrs.contains(&allow))
+                .map(|_| name.chars().take(80).collect::<String>())
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        missing.is_empty(),
+        "items without allowed lints: {missing:#?}"
+    );
+}
+
+#[test]
+fn generated_items_allow_lints() {
+    let wit = "
+        package wasmcloud:settings
+
+        interface values {
+            flags permissions {
+                read,
+                write,
+            }
+
+            variant failure {
+                missing(string),
+                invalid(string),
+            }
+
+            get: func(key: string) -> result<permissions, failure>
+            set: func(key: string, value: string)
+        }
+
+        world settings {
+            import values
+        }
+    ";
+    for opts in [
+        quote!(
+            generate_tests: true,
+            contract_metadata: true,
+            method_skeleton: true,
+            lattice_clients: true,
+            host_error: crate::HostError,
+            entrypoint: true,
+            bench_harness: true,
+            method_concurrency: { "get": 2 },
+            dispatch_fn: true,
+            structured_errors: true,
+            config_map: true,
+            wire_format: "json",
+        ),
+        quote!(
+            codec: crate::Codec,
+            trait_style: "trait_variant",
+            authorize_methods: ["set"],
+            claims: crate::Claims,
+        ),
+        quote!(
+            dispatch: false,
+            mode: "sync",
+            interface_features: { "values": "values" },
+        ),
+        quote!(trait_style: "boxed_future"),
+    ] {
+        let mut file = expand_file(quote!(SettingsProvider, {
+            world: "settings",
+            inline: #wit,
+            #opts
+        }));
+        assert_items_allow_lints(&mut file);
+    }
+}
+
+/// WIT for a world that imports an interface of `<ns>:<package>` and exports another
+fn import_export_wit(ns: &str, package: &str) -> String {
+    format!(
+        "
+        package {ns}:{package}
+
+        interface handler {{
+            on-event: func(event: string)
+        }}
+
+        interface store {{
+            get: func(key: string) -> option<string>
+        }}
+
+        world served {{
+            export handler
+            import store
+        }}
+        "
+    )
+}
+
+#[test]
+fn interfaces_of_included_worlds_are_served() {
+    let file = expand_file(quote!(BundleProvider, {
+        world: "bundle",
+        path: "tests/fixtures/bundle",
+    }));
+    // The exported interface of the included world is not served, as for the world's own exports
+    let mut names = invocation_struct_names(&file);
+    names.sort();
+    assert_eq!(
+        names,
+        ["BundleCacheEvictInvocation", "BundleStoreGetInvocation"]
+    );
+}
+
+#[test]
+fn exported_interfaces_are_not_served() {
+    let wit = import_export_wit("wasmcloud", "events");
+    let file = expand_file(quote!(EventsProvider, {
+        world: "served",
+        inline: #wit,
+    }));
+    assert_eq!(
+        invocation_struct_names(&file),
+        vec!["EventsStoreGetInvocation"]
+    );
+}
+
+#[test]
+fn packages_named_exports_are_not_the_exports_module() {
+    let wit = import_export_wit("wasmcloud", "exports");
+    let file = expand_file(quote!(ExportsProvider, {
+        world: "served",
+        inline: #wit,
+    }));
+    assert_eq!(
+        invocation_struct_names(&file),
+        vec!["ExportsStoreGetInvocation"]
+    );
+}
+
+#[test]
+fn namespaces_named_exports_can_be_given() {
+    let wit = import_export_wit("exports", "events");
+    let file = expand_file(quote!(EventsProvider, {
+        world: "served",
+        inline: #wit,
+        namespace: "exports",
+    }));
+    assert_eq!(
+        invocation_struct_names(&file),
+        vec!["EventsStoreGetInvocation"]
+    );
+}
+
+#[test]
+fn references_to_primitives_are_converted_to_primitives() {
+    for primitive in [
+        "bool", "char", "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "f32", "f64",
+    ] {
+        let ty = Ident::new(primitive, Span::call_site());
+        let expected: syn::Type = syn::parse_quote!(#ty);
+        assert_eq!(owned(quote!(&#ty)), expected, "&{primitive}");
+        assert_eq!(owned(quote!(&'a #ty)), expected, "&'a {primitive}");
+        assert_eq!(
+            owned(quote!(Option<&#ty>)),
+            syn::parse_quote!(Option<#ty>),
+            "Option<&{primitive}>"
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! Arguments that wit-bindgen generates as references are received as owned types
+
+mod common;
+
+use common::sdk::{self, MessageDispatch};
+
+#[derive(Default)]
+struct ShapesProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(ShapesProvider, {
+    world: "shapes",
+    path: "tests/fixtures/shapes",
+    sdk_path: crate::common::sdk,
+});
+
+impl ShapesProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn write(&self, _ctx: sdk::Context, data: Vec<u8>) -> u32 {
+        data.len() as u32
+    }
+
+    async fn delete_many(&self, _ctx: sdk::Context, keys: Vec<String>) -> u32 {
+        keys.len() as u32
+    }
+
+    async fn publish_batch(
+        &self,
+        _ctx: sdk::Context,
+        msgs: Vec<wasmcloud::shapes::args::Message>,
+    ) -> u32 {
+        msgs.iter().map(|m| m.body.len() as u32).sum()
+    }
+
+    async fn swap(&self, _ctx: sdk::Context, (n, s): (u32, String)) -> (String, u32) {
+        (s, n)
+    }
+
+    async fn describe(
+        &self,
+        _ctx: sdk::Context,
+        body: Option<Vec<u8>>,
+        msg: Option<wasmcloud::shapes::args::Message>,
+    ) -> String {
+        format!("{:?} {:?}", body.map(|b| b.len()), msg.map(|m| m.subject))
+    }
+}
+
+/// Dispatch an invocation, decoding its response
+async fn invoke<T: serde::de::DeserializeOwned>(
+    method: &str,
+    invocation: &impl serde::Serialize,
+) -> T {
+    let body = sdk::serialize(invocation).expect("failed to serialize invocation");
+    let resp = ShapesProvider
+        .dispatch(common::ctx("actor"), method.into(), body.into())
+        .await
+        .expect("failed to dispatch invocation");
+    sdk::deserialize(&resp).expect("failed to deserialize response")
+}
+
+#[tokio::test]
+async fn tuple_argument_is_received_owned() {
+    let swapped: (String, u32) = invoke(
+        "Message.Swap",
+        &ShapesArgsSwapInvocation {
+            pair: (1, "one".into()),
+        },
+    )
+    .await;
+    assert_eq!(swapped, ("one".into(), 1));
+}
+
+#[tokio::test]
+async fn bare_slice_argument_is_received_owned() {
+    let written: u32 = invoke(
+        "Message.Write",
+        &ShapesArgsWriteInvocation {
+            data: vec![1, 2, 3, 4],
+        },
+    )
+    .await;
+    assert_eq!(written, 4);
+}
+
+#[tokio::test]
+async fn optional_reference_arguments_are_received_owned() {
+    let described: String = invoke(
+        "Message.Describe",
+        &ShapesArgsDescribeInvocation {
+            body: Some(vec![1, 2, 3]),
+            msg: None,
+        },
+    )
+    .await;
+    assert_eq!(described, "Some(3) None");
+}
+
+#[tokio::test]
+async fn list_of_strings_argument_is_received_owned() {
+    let deleted: u32 = invoke(
+        "Message.DeleteMany",
+        &ShapesArgsDeleteManyInvocation {
+            keys: vec!["a".into(), "b".into()],
+        },
+    )
+    .await;
+    assert_eq!(deleted, 2);
+}
+
+#[tokio::test]
+async fn list_of_records_argument_is_received_owned() {
+    let published: u32 = invoke(
+        "Message.PublishBatch",
+        &ShapesArgsPublishBatchInvocation {
+            msgs: vec![
+                wasmcloud::shapes::args::Message {
+                    subject: "a".into(),
+                    body: vec![1, 2],
+                },
+                wasmcloud::shapes::args::Message {
+                    subject: "b".into(),
+                    body: vec![3],
+                },
+            ],
+        },
+    )
+    .await;
+    assert_eq!(published, 3);
+}
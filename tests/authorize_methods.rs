@@ -0,0 +1,95 @@
+//! Methods named by `authorize_methods` are only dispatched for actors the provider authorizes
+
+mod common;
+
+use std::collections::HashSet;
+
+use common::sdk::{self, error::ProviderInvocationError, MessageDispatch};
+use wasmcloud::keyvalue::store::Metadata;
+
+struct KvProvider {
+    /// Actors allowed to delete keys
+    allowed: HashSet<String>,
+}
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+    authorize_methods: ["delete"],
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn _authorize(&self, ctx: &sdk::Context, method: &str) -> bool {
+        assert_eq!(method, "Message.Delete");
+        ctx.actor
+            .as_ref()
+            .is_some_and(|actor| self.allowed.contains(actor))
+    }
+
+    async fn get(&self, _ctx: sdk::Context, _key: String) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        0
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+fn provider() -> KvProvider {
+    KvProvider {
+        allowed: HashSet::from(["admin".to_string()]),
+    }
+}
+
+async fn delete(provider: &KvProvider, actor: &str) -> Result<Vec<u8>, ProviderInvocationError> {
+    let body = sdk::serialize(&KeyvalueStoreDeleteInvocation { key: "a".into() })
+        .expect("failed to serialize");
+    provider
+        .dispatch(common::ctx(actor), "Message.Delete".into(), body.into())
+        .await
+}
+
+#[tokio::test]
+async fn unauthorized_actor_is_rejected() {
+    assert_eq!(
+        delete(&provider(), "intruder").await,
+        Err(ProviderInvocationError::Provider(
+            "actor [intruder] is not authorized to invoke [Message.Delete]".into()
+        ))
+    );
+}
+
+#[tokio::test]
+async fn authorized_actor_is_dispatched() {
+    assert!(delete(&provider(), "admin").await.is_ok());
+}
+
+#[tokio::test]
+async fn unlisted_methods_are_not_authorized() {
+    let body = sdk::serialize(&KeyvalueStoreCountInvocation {}).expect("failed to serialize");
+    let resp = provider()
+        .dispatch(common::ctx("intruder"), "Message.Count".into(), body.into())
+        .await;
+    assert!(resp.is_ok());
+}
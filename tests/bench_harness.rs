@@ -0,0 +1,85 @@
+//! The benchmark harness generated with `bench_harness` builds and measures dispatch
+
+mod common;
+
+use std::time::Duration;
+
+use common::sdk;
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+    bench_harness: true,
+    bench_cfg: test,
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, _key: String) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        0
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+#[test]
+fn generated_benchmarks_run() {
+    let runtime = tokio::runtime::Runtime::new().expect("failed to build runtime");
+    let mut c = criterion::Criterion::default()
+        .sample_size(10)
+        .warm_up_time(Duration::from_millis(1))
+        .measurement_time(Duration::from_millis(10))
+        .without_plots();
+    dispatch_bench::bench_get(
+        &mut c,
+        &runtime,
+        &KvProvider,
+        &KeyvalueStoreGetInvocation { key: "a".into() },
+    );
+    dispatch_bench::bench_set(
+        &mut c,
+        &runtime,
+        &KvProvider,
+        &KeyvalueStoreSetInvocation {
+            key: "a".into(),
+            value: "1".into(),
+            metadata: Metadata {
+                content_type: "text/plain".into(),
+                max_age: None,
+            },
+        },
+    );
+    dispatch_bench::bench_count(&mut c, &runtime, &KvProvider, &KeyvalueStoreCountInvocation {});
+    dispatch_bench::bench_delete(
+        &mut c,
+        &runtime,
+        &KvProvider,
+        &KeyvalueStoreDeleteInvocation { key: "a".into() },
+    );
+}
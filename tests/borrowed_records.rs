@@ -0,0 +1,79 @@
+//! With borrowing ownership, records of records are received in the owned form that wit-bindgen generates
+
+mod common;
+
+use common::sdk::{self, MessageDispatch};
+use wasmcloud::messaging::consumer::{EnvelopeResult, HeaderResult};
+
+#[derive(Default)]
+struct MessagingProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(MessagingProvider, {
+    inline: "
+        package wasmcloud:messaging
+
+        interface consumer {
+            record header {
+                subject: string,
+                reply-to: option<string>,
+            }
+
+            record envelope {
+                header: header,
+                body: list<u8>,
+            }
+
+            publish: func(msg: envelope) -> u32
+            fetch: func(subject: string) -> option<envelope>
+        }
+
+        world messaging {
+            import consumer
+        }
+    ",
+    ownership: Borrowing { duplicate_if_necessary: true },
+    sdk_path: crate::common::sdk,
+});
+
+impl MessagingProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn publish(&self, _ctx: sdk::Context, msg: EnvelopeResult) -> u32 {
+        (msg.header.subject.len() + msg.body.len()) as u32
+    }
+
+    async fn fetch(&self, _ctx: sdk::Context, subject: String) -> Option<EnvelopeResult> {
+        Some(EnvelopeResult {
+            header: HeaderResult {
+                subject,
+                reply_to: None,
+            },
+            body: vec![],
+        })
+    }
+}
+
+#[tokio::test]
+async fn nested_record_argument_is_received_owned() {
+    let body = sdk::serialize(&MessagingConsumerPublishInvocation {
+        msg: EnvelopeResult {
+            header: HeaderResult {
+                subject: "events".into(),
+                reply_to: Some("inbox".into()),
+            },
+            body: vec![1, 2, 3],
+        },
+    })
+    .expect("failed to serialize invocation");
+    let resp = MessagingProvider
+        .dispatch(common::ctx("actor"), "Message.Publish".into(), body.into())
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(sdk::deserialize::<u32>(&resp), Ok(9));
+}
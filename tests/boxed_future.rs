@@ -0,0 +1,68 @@
+//! Interface traits generated with `trait_style: "boxed_future"` return explicitly boxed futures
+
+mod common;
+
+use std::future::Future;
+use std::pin::Pin;
+
+use common::sdk;
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+    trait_style: "boxed_future",
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+        Ok(Some(key))
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        7
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+#[tokio::test]
+async fn trait_methods_return_boxed_futures() {
+    let provider = KvProvider;
+    let count: Pin<Box<dyn Future<Output = u64> + Send + '_>> =
+        Store::count(&provider, common::ctx("actor"));
+    assert_eq!(count.await, 7);
+
+    let get: Pin<Box<dyn Future<Output = Result<Option<String>, String>> + Send + '_>> =
+        Store::get(&provider, common::ctx("actor"), "a".into());
+    assert_eq!(get.await, Ok(Some("a".into())));
+}
+
+/// The trait is object safe, as its methods return boxed futures rather than being `async`
+#[tokio::test]
+async fn trait_is_usable_as_trait_object() {
+    let store: Box<dyn Store + Send + Sync> = Box::new(KvProvider);
+    assert_eq!(store.count(common::ctx("actor")).await, 7);
+}
@@ -0,0 +1,16 @@
+//! Helpers shared by the tests that build providers with `generate!`
+//!
+//! Generated code is pointed at [`sdk`] (with `sdk_path: crate::common::sdk`), a stand-in for the parts of
+//! `wasmcloud_provider_sdk` that it uses, so that providers can be built and invoked without a lattice.
+
+#![allow(dead_code)]
+
+pub mod sdk;
+
+/// Create a context for an invocation sent by the given actor
+pub fn ctx(actor: &str) -> sdk::Context {
+    sdk::Context {
+        actor: Some(actor.to_string()),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,138 @@
+//! Stand-in for the parts of `wasmcloud_provider_sdk` used by generated providers
+//!
+//! Invocations are (de)serialized with MessagePack (with named fields), as by the SDK.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+pub use async_trait::async_trait;
+
+/// Context of an invocation
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    /// Id of the actor that sent the invocation
+    pub actor: Option<String>,
+
+    /// Tracing information propagated with the invocation
+    pub tracing: HashMap<String, String>,
+}
+
+pub mod core {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, Default)]
+    pub struct LinkDefinition {
+        pub actor_id: String,
+        pub provider_id: String,
+        pub link_name: String,
+        pub contract_id: String,
+        pub values: HashMap<String, String>,
+    }
+
+    #[derive(Debug, Clone, Default)]
+    pub struct HealthCheckRequest {}
+
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct HealthCheckResponse {
+        pub healthy: bool,
+        pub message: Option<String>,
+    }
+
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct WasmCloudEntity {
+        pub public_key: String,
+        pub link_name: String,
+        pub contract_id: String,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct Message<'m> {
+        pub method: &'m str,
+        pub arg: std::borrow::Cow<'m, [u8]>,
+    }
+}
+
+pub mod error {
+    /// Errors (de)serializing invocations
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum InvocationError {
+        Ser(String),
+        Deser(String),
+        Malformed(String),
+    }
+
+    impl std::fmt::Display for InvocationError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{self:?}")
+        }
+    }
+
+    impl std::error::Error for InvocationError {}
+
+    /// Errors handling invocations
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum ProviderInvocationError {
+        Invocation(InvocationError),
+        Provider(String),
+    }
+
+    impl std::fmt::Display for ProviderInvocationError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{self:?}")
+        }
+    }
+
+    impl std::error::Error for ProviderInvocationError {}
+
+    impl From<InvocationError> for ProviderInvocationError {
+        fn from(e: InvocationError) -> Self {
+            ProviderInvocationError::Invocation(e)
+        }
+    }
+
+    pub type ProviderResult<T> = Result<T, ProviderInvocationError>;
+}
+
+/// Serialize data to be sent over the lattice
+pub fn serialize<T: serde::Serialize + ?Sized>(
+    data: &T,
+) -> Result<Vec<u8>, error::InvocationError> {
+    rmp_serde::to_vec_named(data).map_err(|e| error::InvocationError::Ser(e.to_string()))
+}
+
+/// Deserialize data received over the lattice
+pub fn deserialize<'de, T: serde::Deserialize<'de>>(
+    buf: &'de [u8],
+) -> Result<T, error::InvocationError> {
+    rmp_serde::from_slice(buf).map_err(|e| error::InvocationError::Deser(e.to_string()))
+}
+
+#[async_trait]
+pub trait MessageDispatch {
+    async fn dispatch<'a>(
+        &'a self,
+        ctx: Context,
+        method: String,
+        body: Cow<'a, [u8]>,
+    ) -> Result<Vec<u8>, error::ProviderInvocationError>;
+}
+
+#[async_trait]
+pub trait ProviderHandler: Sync {
+    async fn put_link(&self, _ld: &core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn delete_link(&self, _actor_id: &str) {}
+
+    async fn shutdown(&self) {}
+
+    async fn health_request(&self, _arg: &core::HealthCheckRequest) -> core::HealthCheckResponse {
+        core::HealthCheckResponse {
+            healthy: true,
+            message: None,
+        }
+    }
+}
+
+pub trait Provider: MessageDispatch + ProviderHandler + Send + Sync + 'static {}
@@ -0,0 +1,107 @@
+//! Providers generated with `dispatch: false` implement `MessageDispatch` themselves
+
+mod common;
+
+use common::sdk::{self, MessageDispatch, ProviderHandler};
+use std::borrow::Cow;
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+    dispatch: false,
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+        Ok(Some(key))
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        7
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+/// The provider's own operations, which no generated `Operation` enum clashes with
+#[derive(Debug, PartialEq)]
+enum Operation {
+    Count,
+}
+
+impl Operation {
+    fn parse(method: &str) -> Option<Self> {
+        (method == "Message.Count").then_some(Operation::Count)
+    }
+}
+
+/// Routes only `Count`, which would conflict with a generated `MessageDispatch` impl
+#[sdk::async_trait]
+impl MessageDispatch for KvProvider {
+    async fn dispatch<'a>(
+        &'a self,
+        ctx: sdk::Context,
+        method: String,
+        _body: Cow<'a, [u8]>,
+    ) -> Result<Vec<u8>, sdk::error::ProviderInvocationError> {
+        match Operation::parse(&method) {
+            Some(Operation::Count) => sdk::serialize(&self.count(ctx).await)
+                .map_err(sdk::error::ProviderInvocationError::Invocation),
+            None => Err(sdk::error::ProviderInvocationError::Provider(format!(
+                "unsupported method {method}"
+            ))),
+        }
+    }
+}
+
+#[tokio::test]
+async fn custom_dispatch_is_used() {
+    let provider = KvProvider;
+    let resp = provider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Count".into(),
+            Cow::Borrowed(&[]),
+        )
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(sdk::deserialize::<u64>(&resp), Ok(7));
+    assert!(provider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Get".into(),
+            Cow::Borrowed(&[])
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn lifecycle_impls_are_still_generated() {
+    fn assert_provider<P: sdk::Provider>() {}
+    assert_provider::<KvProvider>();
+    assert!(KvProvider.put_link(&Default::default()).await);
+}
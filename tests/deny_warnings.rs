@@ -0,0 +1,65 @@
+//! Generated code builds in providers that deny warnings, while their own code is still linted
+
+#![deny(warnings, clippy::all)]
+
+mod common;
+
+use common::sdk;
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+    contract_metadata: true,
+    lattice_method_style: "wit",
+    method_skeleton: true,
+    generate_tests: true,
+    dispatch_fn: true,
+    method_concurrency: { "set": 1 },
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+        Ok(Some(key))
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        0
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+#[test]
+fn generated_code_builds_without_warnings() {
+    assert_eq!(
+        PROVIDER_CONTRACT,
+        concat!(
+            r#"{"interfaces":[{"namespace":"wasmcloud","package":"keyvalue","version":null,"interface":"store","#,
+            r#""operations":["wasmcloud:keyvalue/store.get","wasmcloud:keyvalue/store.set","#,
+            r#""wasmcloud:keyvalue/store.count","wasmcloud:keyvalue/store.delete"]}]}"#,
+        )
+    );
+}
@@ -0,0 +1,76 @@
+//! The free function generated with `dispatch_fn` dispatches invocations like `MessageDispatch::dispatch`
+
+mod common;
+
+use common::sdk;
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+    dispatch_fn: true,
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+        Ok(Some(key))
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        0
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+#[tokio::test]
+async fn free_function_dispatches_invocations() {
+    let body = sdk::serialize(&KeyvalueStoreGetInvocation { key: "a".into() })
+        .expect("failed to serialize");
+    let resp = handle_kv_provider(
+        &KvProvider,
+        common::ctx("actor"),
+        "Message.Get".into(),
+        &body,
+    )
+    .await
+    .expect("failed to handle invocation");
+    assert_eq!(
+        sdk::deserialize::<Option<String>>(&resp),
+        Ok(Some("a".into()))
+    );
+}
+
+#[tokio::test]
+async fn free_function_rejects_unknown_methods() {
+    let resp = handle_kv_provider(
+        &KvProvider,
+        common::ctx("actor"),
+        "Message.Nope".into(),
+        &[],
+    )
+    .await;
+    assert!(resp.is_err());
+}
@@ -0,0 +1,92 @@
+//! Worlds that import nothing (ex. while scaffolding a provider, before its interfaces are imported) produce a
+//! provider that handles no methods
+
+mod common;
+
+use common::sdk::{self, MessageDispatch};
+use std::borrow::Cow;
+
+#[derive(Default)]
+struct ScaffoldProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(ScaffoldProvider, {
+    inline: "
+        package wasmcloud:scaffold
+
+        world scaffold {}
+    ",
+    sdk_path: crate::common::sdk,
+});
+
+impl ScaffoldProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+}
+
+/// Provider of a world that only exports an interface, which the provider doesn't serve
+#[derive(Default)]
+struct ExportingProvider;
+
+mod exporting {
+    wit_bindgen_wasmcloud_provider_guest::generate!(super::ExportingProvider, {
+        inline: "
+            package wasmcloud:exporting
+
+            interface handler {
+                on-event: func(event: string)
+            }
+
+            world exporting {
+                export handler
+            }
+        ",
+        sdk_path: crate::common::sdk,
+    });
+}
+
+impl ExportingProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+}
+
+#[tokio::test]
+async fn providers_of_empty_worlds_reject_every_invocation() {
+    fn assert_provider<P: sdk::Provider>() {}
+    assert_provider::<ScaffoldProvider>();
+
+    assert_eq!(Operation::NAMES, &[] as &[&str]);
+    assert!(ScaffoldProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Get".into(),
+            Cow::Borrowed(&[])
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn providers_of_export_only_worlds_reject_every_invocation() {
+    fn assert_provider<P: sdk::Provider>() {}
+    assert_provider::<ExportingProvider>();
+
+    assert_eq!(exporting::Operation::NAMES, &[] as &[&str]);
+    assert!(ExportingProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.OnEvent".into(),
+            Cow::Borrowed(&[])
+        )
+        .await
+        .is_err());
+}
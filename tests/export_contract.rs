@@ -0,0 +1,55 @@
+//! `export_contract!` accepts providers that implement everything a wasmCloud provider requires
+
+mod common;
+
+use common::sdk;
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+});
+
+wit_bindgen_wasmcloud_provider_guest::export_contract!(KvProvider, sdk_path: crate::common::sdk);
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+        Ok(Some(key))
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        0
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+#[test]
+fn provider_is_a_wasmcloud_provider() {
+    fn is_provider<P: sdk::Provider>(_: &P) -> bool {
+        true
+    }
+    assert!(is_provider(&KvProvider));
+}
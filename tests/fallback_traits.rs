@@ -0,0 +1,219 @@
+//! The fallbacks for optional provider methods are scoped to the generated code, so they neither collide with
+//! the provider's own items nor take precedence over the provider's own methods
+
+mod common;
+
+use common::sdk;
+use common::sdk::{MessageDispatch, ProviderHandler};
+use std::borrow::Cow;
+
+/// Provider relying on the fallbacks, alongside items sharing the names of the fallback traits
+mod defaults {
+    use crate::common::sdk;
+    use wasmcloud::keyvalue::store::Metadata;
+
+    #[allow(dead_code)]
+    pub struct DefaultHealthRequest;
+
+    #[allow(dead_code)]
+    pub trait DefaultDispatchHooks {}
+
+    #[derive(Default)]
+    pub struct KvProvider;
+
+    wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+        sdk_path: crate::common::sdk,
+    });
+
+    /// Body of an invocation of `delete`
+    pub fn delete_body() -> Vec<u8> {
+        sdk::serialize(&KeyvalueStoreDeleteInvocation { key: "a".into() })
+            .expect("failed to serialize")
+    }
+
+    impl KvProvider {
+        async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+            true
+        }
+
+        async fn _delete_link(&self, _actor_id: &str) {}
+
+        async fn _shutdown(&self) {}
+
+        async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+            Ok(Some(key))
+        }
+
+        async fn set(
+            &self,
+            _ctx: sdk::Context,
+            _key: String,
+            _value: String,
+            _metadata: Metadata,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+
+        async fn count(&self, _ctx: sdk::Context) -> u64 {
+            0
+        }
+
+        async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+    }
+}
+
+/// Provider defining its own health check and dispatch hooks
+mod overridden {
+    use crate::common::sdk;
+    use wasmcloud::keyvalue::store::Metadata;
+
+    #[derive(Default)]
+    pub struct KvProvider;
+
+    wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+        sdk_path: crate::common::sdk,
+    });
+
+    /// Body of an invocation of `delete`
+    pub fn delete_body() -> Vec<u8> {
+        sdk::serialize(&KeyvalueStoreDeleteInvocation { key: "a".into() })
+            .expect("failed to serialize")
+    }
+
+    impl KvProvider {
+        async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+            true
+        }
+
+        async fn _delete_link(&self, _actor_id: &str) {}
+
+        async fn _shutdown(&self) {}
+
+        async fn _health_request(
+            &self,
+            _arg: &sdk::core::HealthCheckRequest,
+        ) -> sdk::core::HealthCheckResponse {
+            sdk::core::HealthCheckResponse {
+                healthy: false,
+                message: Some("draining".into()),
+            }
+        }
+
+        async fn _before_dispatch(
+            &self,
+            _ctx: &sdk::Context,
+            method: &str,
+        ) -> Result<(), sdk::error::ProviderInvocationError> {
+            match method.parse::<Operation>() {
+                Ok(Operation::Delete) => Err(sdk::error::ProviderInvocationError::Provider(
+                    "deletes are disabled".into(),
+                )),
+                Ok(_) => Ok(()),
+                Err(_) => Err(sdk::error::ProviderInvocationError::Provider(format!(
+                    "rejected unknown method {method}"
+                ))),
+            }
+        }
+
+        async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+            Ok(Some(key))
+        }
+
+        async fn set(
+            &self,
+            _ctx: sdk::Context,
+            _key: String,
+            _value: String,
+            _metadata: Metadata,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+
+        async fn count(&self, _ctx: sdk::Context) -> u64 {
+            0
+        }
+
+        async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+    }
+}
+
+#[tokio::test]
+async fn fallbacks_report_healthy_and_dispatch() {
+    let resp = defaults::KvProvider
+        .health_request(&sdk::core::HealthCheckRequest::default())
+        .await;
+    assert!(resp.healthy);
+
+    let body = defaults::delete_body();
+    defaults::KvProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Delete".into(),
+            Cow::Borrowed(&body),
+        )
+        .await
+        .expect("failed to dispatch");
+}
+
+#[tokio::test]
+async fn provider_methods_take_precedence_over_fallbacks() {
+    let resp = overridden::KvProvider
+        .health_request(&sdk::core::HealthCheckRequest::default())
+        .await;
+    assert_eq!(
+        resp,
+        sdk::core::HealthCheckResponse {
+            healthy: false,
+            message: Some("draining".into()),
+        }
+    );
+
+    let body = overridden::delete_body();
+    let resp = overridden::KvProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Delete".into(),
+            Cow::Borrowed(&body),
+        )
+        .await;
+    assert_eq!(
+        resp,
+        Err(sdk::error::ProviderInvocationError::Provider(
+            "deletes are disabled".into()
+        ))
+    );
+}
+
+#[tokio::test]
+async fn hooks_see_methods_the_provider_does_not_handle() {
+    let resp = overridden::KvProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Purge".into(),
+            Cow::Borrowed(&[][..]),
+        )
+        .await;
+    assert_eq!(
+        resp,
+        Err(sdk::error::ProviderInvocationError::Provider(
+            "rejected unknown method Message.Purge".into()
+        ))
+    );
+
+    // Without hooks of its own, the invocation is rejected as malformed
+    let resp = defaults::KvProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Purge".into(),
+            Cow::Borrowed(&[][..]),
+        )
+        .await;
+    assert!(matches!(
+        resp,
+        Err(sdk::error::ProviderInvocationError::Invocation(_))
+    ));
+}
@@ -0,0 +1,90 @@
+//! Methods of WIT functions returning a `result` are fallible, while the values of others are sent as-is
+
+mod common;
+
+use common::sdk::{self, MessageDispatch};
+use std::borrow::Cow;
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+        match key.as_str() {
+            "missing" => Err(format!("no such key {key}")),
+            _ => Ok(Some(key)),
+        }
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        42
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+/// Dispatch a `Get` of the given key
+async fn get(key: &str) -> Result<Vec<u8>, sdk::error::ProviderInvocationError> {
+    let body = sdk::serialize(&KeyvalueStoreGetInvocation { key: key.into() })
+        .expect("failed to serialize invocation");
+    KvProvider
+        .dispatch(common::ctx("actor"), "Message.Get".into(), body.into())
+        .await
+}
+
+#[tokio::test]
+async fn infallible_method_value_is_sent() {
+    let resp = KvProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Count".into(),
+            Cow::Borrowed(&[]),
+        )
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(sdk::deserialize::<u64>(&resp), Ok(42));
+}
+
+#[tokio::test]
+async fn fallible_method_ok_value_is_sent() {
+    let resp = get("key").await.expect("failed to dispatch");
+    assert_eq!(
+        sdk::deserialize::<Option<String>>(&resp),
+        Ok(Some("key".into()))
+    );
+}
+
+#[tokio::test]
+async fn fallible_method_error_is_returned() {
+    assert_eq!(
+        get("missing").await,
+        Err(sdk::error::ProviderInvocationError::Provider(
+            "no such key missing".into()
+        ))
+    );
+}
@@ -0,0 +1,79 @@
+//! Records with fields marked by `flatten_fields` are received as flat payloads
+
+mod common;
+
+use common::sdk::{self, MessageDispatch};
+use wasmcloud::settings::store::{Config, Limits};
+
+#[derive(Default)]
+struct SettingsProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(SettingsProvider, {
+    world: "settings",
+    path: "tests/fixtures/settings",
+    sdk_path: crate::common::sdk,
+    flatten_fields: ["config.limits"],
+});
+
+impl SettingsProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn apply(&self, _ctx: sdk::Context, config: Config) -> u32 {
+        config.limits.connections
+    }
+}
+
+#[test]
+fn flattened_field_deserializes_from_flat_payload() {
+    let config: Config = serde_json::from_value(serde_json::json!({
+        "name": "primary",
+        "connections": 4,
+        "timeout": 30,
+    }))
+    .expect("failed to deserialize flat config");
+    assert_eq!(config.name, "primary");
+    assert_eq!(config.limits.connections, 4);
+    assert_eq!(config.limits.timeout, 30);
+}
+
+#[test]
+fn flattened_field_serializes_to_flat_payload() {
+    let config = Config {
+        name: "primary".into(),
+        limits: Limits {
+            connections: 4,
+            timeout: 30,
+        },
+    };
+    assert_eq!(
+        serde_json::to_value(&config).expect("failed to serialize config"),
+        serde_json::json!({
+            "name": "primary",
+            "connections": 4,
+            "timeout": 30,
+        })
+    );
+}
+
+#[tokio::test]
+async fn invocation_with_flat_payload_is_dispatched() {
+    let body = rmp_serde::to_vec_named(&serde_json::json!({
+        "config": {
+            "name": "primary",
+            "connections": 4,
+            "timeout": 30,
+        },
+    }))
+    .expect("failed to serialize invocation");
+    let resp = SettingsProvider
+        .dispatch(common::ctx("actor"), "Message.Apply".into(), body.into())
+        .await
+        .expect("failed to dispatch invocation");
+    assert_eq!(sdk::deserialize::<u32>(&resp), Ok(4));
+}
@@ -0,0 +1,116 @@
+//! Providers may be generic (ex. over the backend that stores their data), with bounds given inline or in a
+//! where clause
+
+mod common;
+
+use common::sdk::{self, MessageDispatch};
+use std::{borrow::Cow, collections::HashMap, sync::Mutex};
+use wasmcloud::keyvalue::store::Metadata;
+
+/// Storage that a provider can be backed by
+trait Backend: Default + Send + Sync + 'static {
+    fn load(&self, key: &str) -> Option<String>;
+    fn store(&self, key: String, value: String);
+}
+
+#[derive(Default)]
+struct MemoryBackend(Mutex<HashMap<String, String>>);
+
+impl Backend for MemoryBackend {
+    fn load(&self, key: &str) -> Option<String> {
+        self.0.lock().unwrap().get(key).cloned()
+    }
+
+    fn store(&self, key: String, value: String) {
+        self.0.lock().unwrap().insert(key, value);
+    }
+}
+
+#[derive(Default)]
+struct KvProvider<B> {
+    backend: B,
+}
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider<B> where B: Backend, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+    method_skeleton: true,
+});
+
+impl<B: Backend> KvProvider<B> {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+        Ok(self.backend.load(&key))
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        key: String,
+        value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        self.backend.store(key, value);
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        0
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+#[tokio::test]
+async fn generic_provider_dispatches_to_its_backend() {
+    fn assert_provider<P: sdk::Provider>() {}
+    assert_provider::<KvProvider<MemoryBackend>>();
+
+    let provider = KvProvider::<MemoryBackend>::default();
+    let set = KeyvalueStoreSetInvocation {
+        key: "greeting".into(),
+        value: "hello".into(),
+        metadata: Metadata {
+            content_type: "text/plain".into(),
+            max_age: None,
+        },
+    };
+    provider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Set".into(),
+            Cow::Owned(sdk::serialize(&set).unwrap()),
+        )
+        .await
+        .expect("failed to dispatch set");
+    let resp = provider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Get".into(),
+            Cow::Owned(
+                sdk::serialize(&KeyvalueStoreGetInvocation {
+                    key: "greeting".into(),
+                })
+                .unwrap(),
+            ),
+        )
+        .await
+        .expect("failed to dispatch get");
+    assert_eq!(
+        sdk::deserialize::<Option<String>>(&resp),
+        Ok(Some("hello".into()))
+    );
+}
+
+#[test]
+fn skeleton_keeps_the_where_clause() {
+    assert!(PROVIDER_METHOD_SKELETON.starts_with("impl<B> KvProvider<B> where B: Backend {"));
+}
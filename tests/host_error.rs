@@ -0,0 +1,70 @@
+//! Host clients generated with `host_error` convert the errors of host calls into the provider's error type
+
+mod common;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use common::sdk;
+
+/// Whether the (stand-in) host reports that it is ready
+static HOST_READY: AtomicBool = AtomicBool::new(false);
+
+/// Stand-in for the host's implementation of `wasmcloud:host/calls.ready`, which returns
+/// the discriminant of the `result` (`0` for `ok`, `1` for `err`)
+#[export_name = "wasmcloud:host/calls_ready"]
+extern "C" fn host_ready() -> i32 {
+    if HOST_READY.load(Ordering::SeqCst) {
+        0
+    } else {
+        1
+    }
+}
+
+/// Error type of the provider
+#[derive(Debug, PartialEq, Eq)]
+struct ProviderError(String);
+
+impl From<()> for ProviderError {
+    fn from(_: ()) -> Self {
+        ProviderError("host is not ready".into())
+    }
+}
+
+#[derive(Default)]
+struct HostProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(HostProvider, {
+    world: "host",
+    path: "tests/fixtures/host",
+    sdk_path: crate::common::sdk,
+    host_error: crate::ProviderError,
+});
+
+impl HostProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn ready(&self, _ctx: sdk::Context) -> Result<(), ()> {
+        Ok(())
+    }
+}
+
+/// Check readiness with the host, using `?` on the host call
+fn check_host() -> Result<&'static str, ProviderError> {
+    CallsHostClient::ready()?;
+    Ok("ready")
+}
+
+#[test]
+fn failing_host_call_converts_error() {
+    HOST_READY.store(false, Ordering::SeqCst);
+    assert_eq!(check_host(), Err(ProviderError("host is not ready".into())));
+
+    HOST_READY.store(true, Ordering::SeqCst);
+    assert_eq!(check_host(), Ok("ready"));
+}
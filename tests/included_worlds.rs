@@ -0,0 +1,76 @@
+//! Interfaces that a world imports through another world it includes are served like its own
+
+mod common;
+
+use common::sdk::{self, MessageDispatch};
+use std::borrow::Cow;
+
+#[derive(Default)]
+struct BundleProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(BundleProvider, {
+    world: "bundle",
+    path: "tests/fixtures/bundle",
+    sdk_path: crate::common::sdk,
+});
+
+impl BundleProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Option<String> {
+        Some(key)
+    }
+
+    async fn evict(&self, _ctx: sdk::Context, key: String) -> bool {
+        key == "cached"
+    }
+}
+
+#[tokio::test]
+async fn methods_of_included_interfaces_are_dispatched() {
+    let body = sdk::serialize(&BundleStoreGetInvocation { key: "a".into() })
+        .expect("failed to serialize invocation");
+    let resp = BundleProvider
+        .dispatch(common::ctx("actor"), "Message.Get".into(), Cow::Owned(body))
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(
+        sdk::deserialize::<Option<String>>(&resp),
+        Ok(Some("a".into()))
+    );
+}
+
+#[tokio::test]
+async fn methods_of_the_world_are_dispatched() {
+    let body = sdk::serialize(&BundleCacheEvictInvocation {
+        key: "cached".into(),
+    })
+    .expect("failed to serialize invocation");
+    let resp = BundleProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Evict".into(),
+            Cow::Owned(body),
+        )
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(sdk::deserialize::<bool>(&resp), Ok(true));
+}
+
+#[tokio::test]
+async fn exports_of_included_worlds_are_not_dispatched() {
+    assert!(BundleProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.OnEvict".into(),
+            Cow::Borrowed(&[])
+        )
+        .await
+        .is_err());
+}
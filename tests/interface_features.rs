@@ -0,0 +1,85 @@
+//! Interfaces may be gated behind features of the provider, whose methods are then neither required nor
+//! dispatched when the feature is disabled (as `cache` is here, unless tested with
+//! `RUSTFLAGS='--cfg feature="cache"' cargo test --test interface_features`)
+
+mod common;
+
+use common::sdk::{self, error::ProviderInvocationError, MessageDispatch};
+use std::borrow::Cow;
+
+#[derive(Default)]
+struct KvProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    inline: "
+        package wasmcloud:keyvalue
+
+        interface store {
+            get: func(key: string) -> option<string>
+        }
+
+        interface cache {
+            evict: func(key: string) -> bool
+        }
+
+        world keyvalue {
+            import store
+            import cache
+        }
+    ",
+    sdk_path: crate::common::sdk,
+    interface_features: { "cache": "cache" },
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Option<String> {
+        Some(key)
+    }
+
+    #[cfg(feature = "cache")]
+    async fn evict(&self, _ctx: sdk::Context, _key: String) -> bool {
+        true
+    }
+}
+
+/// Dispatch an invocation of the given method, with a `key` argument
+async fn dispatch(method: &str, key: &str) -> Result<Vec<u8>, ProviderInvocationError> {
+    let body = sdk::serialize(&KeyvalueStoreGetInvocation { key: key.into() }).unwrap();
+    KvProvider
+        .dispatch(common::ctx("actor"), method.into(), Cow::Owned(body))
+        .await
+}
+
+#[tokio::test]
+async fn ungated_methods_are_dispatched() {
+    let resp = dispatch("Message.Get", "a")
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(
+        sdk::deserialize::<Option<String>>(&resp),
+        Ok(Some("a".into()))
+    );
+}
+
+#[cfg(not(feature = "cache"))]
+#[tokio::test]
+async fn methods_of_disabled_interfaces_are_not_dispatched() {
+    assert!(dispatch("Message.Evict", "a").await.is_err());
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn methods_of_enabled_interfaces_are_dispatched() {
+    let resp = dispatch("Message.Evict", "a")
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(sdk::deserialize::<bool>(&resp), Ok(true));
+}
@@ -0,0 +1,83 @@
+//! Traits implemented by the generated invocation structs
+
+mod common;
+
+use common::sdk::{self, MessageDispatch};
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, _key: String) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        3
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+#[test]
+fn invocations_with_default_members_implement_default() {
+    let invocation = KeyvalueStoreGetInvocation {
+        ..Default::default()
+    };
+    assert_eq!(invocation.key, "");
+}
+
+#[test]
+fn invocations_are_clone_and_debug() {
+    let invocation = KeyvalueStoreSetInvocation {
+        key: "a".into(),
+        value: "b".into(),
+        metadata: Metadata {
+            content_type: "text/plain".into(),
+            max_age: Some(60),
+        },
+    };
+    let retained = invocation.clone();
+    drop(invocation);
+    assert_eq!(retained.key, "a");
+    assert_eq!(retained.metadata.max_age, Some(60));
+    assert!(format!("{retained:?}").contains("text/plain"));
+}
+
+#[tokio::test]
+async fn nullary_methods_are_dispatched_without_payload() {
+    let resp = KvProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Count".into(),
+            Vec::new().into(),
+        )
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(sdk::deserialize::<u64>(&resp), Ok(3));
+}
@@ -0,0 +1,120 @@
+//! Providers generated with `config` receive the config values of their links decoded into the config type
+
+mod common;
+
+use common::sdk::{self, ProviderHandler};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+struct LinkConfig {
+    url: String,
+    #[serde(default)]
+    bucket: String,
+}
+
+#[derive(Default)]
+struct KvProvider {
+    configs: Mutex<HashMap<String, LinkConfig>>,
+}
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+    config: crate::LinkConfig,
+});
+
+impl KvProvider {
+    async fn _put_link(&self, config: LinkConfig, ld: &sdk::core::LinkDefinition) -> bool {
+        self.configs
+            .lock()
+            .unwrap()
+            .insert(ld.actor_id.clone(), config);
+        true
+    }
+
+    async fn _delete_link(&self, actor_id: &str) {
+        self.configs.lock().unwrap().remove(actor_id);
+    }
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+        Ok(Some(key))
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: wasmcloud::keyvalue::store::Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        self.configs.lock().unwrap().len() as u64
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+fn link(actor_id: &str, values: &[(&str, &str)]) -> sdk::core::LinkDefinition {
+    sdk::core::LinkDefinition {
+        actor_id: actor_id.into(),
+        link_name: "default".into(),
+        values: values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn link_config_is_decoded() {
+    let provider = KvProvider::default();
+    assert!(
+        provider
+            .put_link(&link(
+                "actor",
+                &[("url", "redis://localhost"), ("bucket", "b")]
+            ))
+            .await
+    );
+    assert_eq!(
+        provider.configs.lock().unwrap().get("actor"),
+        Some(&LinkConfig {
+            url: "redis://localhost".into(),
+            bucket: "b".into(),
+        })
+    );
+}
+
+#[tokio::test]
+async fn optional_link_config_values_may_be_missing() {
+    let provider = KvProvider::default();
+    assert!(
+        provider
+            .put_link(&link("actor", &[("url", "redis://localhost")]))
+            .await
+    );
+    assert_eq!(
+        provider
+            .configs
+            .lock()
+            .unwrap()
+            .get("actor")
+            .map(|c| c.bucket.as_str()),
+        Some("")
+    );
+}
+
+#[tokio::test]
+async fn links_with_invalid_config_are_rejected() {
+    let provider = KvProvider::default();
+    assert!(!provider.put_link(&link("actor", &[("bucket", "b")])).await);
+    assert!(provider.configs.lock().unwrap().is_empty());
+}
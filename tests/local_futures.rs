@@ -0,0 +1,74 @@
+//! With `local_futures`, the per-interface traits accept methods whose futures are not `Send`
+
+mod common;
+
+use common::sdk::{self, MessageDispatch};
+use std::{borrow::Cow, rc::Rc};
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+    dispatch: false,
+    local_futures: true,
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    /// Holds an `Rc` across an await, so the future is not `Send`
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+        let key = Rc::new(key);
+        tokio::task::yield_now().await;
+        Ok(Some(key.to_uppercase()))
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        0
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+/// Invocations are run by the provider itself (ex. on a `LocalSet`), so the SDK's dispatch rejects them
+#[sdk::async_trait]
+impl MessageDispatch for KvProvider {
+    async fn dispatch<'a>(
+        &'a self,
+        _ctx: sdk::Context,
+        method: String,
+        _body: Cow<'a, [u8]>,
+    ) -> Result<Vec<u8>, sdk::error::ProviderInvocationError> {
+        Err(sdk::error::ProviderInvocationError::Provider(format!(
+            "{method} is handled locally"
+        )))
+    }
+}
+
+#[tokio::test]
+async fn methods_with_local_futures_are_callable_through_their_trait() {
+    let resp = tokio::task::LocalSet::new()
+        .run_until(Store::get(&KvProvider, common::ctx("actor"), "a".into()))
+        .await;
+    assert_eq!(resp, Ok(Some("A".into())));
+}
@@ -0,0 +1,136 @@
+//! Arguments that are lists of key/value pairs are received as owned pairs, or as maps with `map_lists`
+
+mod common;
+
+use common::sdk::{self, MessageDispatch};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Provider receiving key/value lists as pairs (the default)
+mod pairs {
+    use crate::common::sdk;
+
+    #[derive(Default)]
+    pub struct ConfigProvider;
+
+    wit_bindgen_wasmcloud_provider_guest::generate!(ConfigProvider, {
+        inline: "
+            package wasmcloud:config
+
+            interface values {
+                apply: func(values: list<tuple<string, string>>) -> string
+            }
+
+            world config {
+                import values
+            }
+        ",
+        sdk_path: crate::common::sdk,
+    });
+
+    impl ConfigProvider {
+        async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+            true
+        }
+
+        async fn _delete_link(&self, _actor_id: &str) {}
+
+        async fn _shutdown(&self) {}
+
+        async fn apply(&self, _ctx: sdk::Context, values: Vec<(String, String)>) -> String {
+            values
+                .iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect::<Vec<_>>()
+                .join(",")
+        }
+    }
+}
+
+/// Provider receiving key/value lists as maps
+mod maps {
+    use crate::common::sdk;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    pub struct ConfigProvider;
+
+    wit_bindgen_wasmcloud_provider_guest::generate!(ConfigProvider, {
+        inline: "
+            package wasmcloud:config
+
+            interface values {
+                apply: func(values: list<tuple<string, string>>) -> string
+            }
+
+            world config {
+                import values
+            }
+        ",
+        sdk_path: crate::common::sdk,
+        map_lists: true,
+    });
+
+    impl ConfigProvider {
+        async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+            true
+        }
+
+        async fn _delete_link(&self, _actor_id: &str) {}
+
+        async fn _shutdown(&self) {}
+
+        async fn apply(&self, _ctx: sdk::Context, values: HashMap<String, String>) -> String {
+            let mut values = values
+                .iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect::<Vec<_>>();
+            values.sort();
+            values.join(",")
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct ApplyPairs {
+    values: Vec<(String, String)>,
+}
+
+#[derive(serde::Serialize)]
+struct ApplyMap {
+    values: HashMap<String, String>,
+}
+
+#[tokio::test]
+async fn key_value_lists_are_received_as_owned_pairs() {
+    let body = sdk::serialize(&ApplyPairs {
+        values: vec![("a".into(), "1".into()), ("b".into(), "2".into())],
+    })
+    .expect("failed to serialize");
+    let resp = pairs::ConfigProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Apply".into(),
+            Cow::Borrowed(&body),
+        )
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(sdk::deserialize::<String>(&resp), Ok("a=1,b=2".into()));
+}
+
+#[tokio::test]
+async fn key_value_lists_are_received_as_maps() {
+    let body = sdk::serialize(&ApplyMap {
+        values: HashMap::from([("b".into(), "2".into()), ("a".into(), "1".into())]),
+    })
+    .expect("failed to serialize");
+    let resp = maps::ConfigProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Apply".into(),
+            Cow::Borrowed(&body),
+        )
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(sdk::deserialize::<String>(&resp), Ok("a=1,b=2".into()));
+}
@@ -0,0 +1,117 @@
+//! Methods named by `method_concurrency` are limited to the given number of concurrent executions,
+//! across every instance of the provider
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::sdk::{self, MessageDispatch};
+use wasmcloud::keyvalue::store::Metadata;
+
+/// Number of concurrent executions allowed for `get`
+const GET_LIMIT: usize = 2;
+
+/// Number of `get`s currently executing, across every instance
+static RUNNING_ALL: AtomicUsize = AtomicUsize::new(0);
+
+/// Highest number of `get`s that executed concurrently, across every instance
+static MAX_RUNNING_ALL: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Default)]
+struct KvProvider {
+    /// Number of `get`s currently executing
+    running: AtomicUsize,
+
+    /// Highest number of `get`s that executed concurrently
+    max_running: AtomicUsize,
+}
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+    method_concurrency: { "get": 2 },
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, _key: String) -> Result<Option<String>, String> {
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_running.fetch_max(running, Ordering::SeqCst);
+        let running_all = RUNNING_ALL.fetch_add(1, Ordering::SeqCst) + 1;
+        MAX_RUNNING_ALL.fetch_max(running_all, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        RUNNING_ALL.fetch_sub(1, Ordering::SeqCst);
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        Ok(None)
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        0
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+/// Dispatch `count` concurrent `get`s to each of the given providers, waiting for all of them
+async fn dispatch_gets(providers: &[Arc<KvProvider>], count: usize) {
+    let body = sdk::serialize(&KeyvalueStoreGetInvocation { key: "a".into() })
+        .expect("failed to serialize");
+    let tasks = providers
+        .iter()
+        .flat_map(|provider| std::iter::repeat(provider).take(count))
+        .map(|provider| {
+            let provider = provider.clone();
+            let body = body.clone();
+            tokio::spawn(async move {
+                provider
+                    .dispatch(common::ctx("actor"), "Message.Get".into(), body.into())
+                    .await
+            })
+        })
+        .collect::<Vec<_>>();
+    for task in tasks {
+        task.await
+            .expect("dispatch panicked")
+            .expect("failed to dispatch");
+    }
+}
+
+// Instances share the semaphore, so they are covered by the same test (tests run in parallel)
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_invocations_are_limited() {
+    let provider = Arc::new(KvProvider::default());
+    dispatch_gets(&[provider.clone()], 8).await;
+    assert_eq!(provider.max_running.load(Ordering::SeqCst), GET_LIMIT);
+
+    // The limit is process-wide, so it is shared by instances rather than applying to each
+    MAX_RUNNING_ALL.store(0, Ordering::SeqCst);
+    let providers = [
+        Arc::new(KvProvider::default()),
+        Arc::new(KvProvider::default()),
+    ];
+    dispatch_gets(&providers, 4).await;
+    assert_eq!(MAX_RUNNING_ALL.load(Ordering::SeqCst), GET_LIMIT);
+    for provider in providers {
+        assert!(provider.max_running.load(Ordering::SeqCst) >= 1);
+    }
+}
@@ -0,0 +1,85 @@
+//! Method names are remapped by the function given with `method_rewrite` before being dispatched
+
+mod common;
+
+use common::sdk;
+use common::sdk::MessageDispatch;
+use std::borrow::Cow;
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider;
+
+/// Resolve the `Fetch` alias of `Get`, leaving other method names as-is
+fn resolve_alias(method: &str) -> Cow<'_, str> {
+    match method {
+        "Message.Fetch" => Cow::Borrowed("Message.Get"),
+        other => Cow::Borrowed(other),
+    }
+}
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+    method_rewrite: resolve_alias,
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+        Ok(Some(key))
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        0
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+async fn get(method: &str) -> Result<Vec<u8>, sdk::error::ProviderInvocationError> {
+    let body = sdk::serialize(&KeyvalueStoreGetInvocation { key: "a".into() })
+        .expect("failed to serialize");
+    KvProvider
+        .dispatch(common::ctx("actor"), method.into(), Cow::Borrowed(&body))
+        .await
+}
+
+#[tokio::test]
+async fn aliases_are_dispatched_to_their_method() {
+    let resp = get("Message.Fetch")
+        .await
+        .expect("failed to dispatch alias");
+    assert_eq!(
+        sdk::deserialize::<Option<String>>(&resp),
+        Ok(Some("a".into()))
+    );
+}
+
+#[tokio::test]
+async fn other_methods_are_dispatched_as_is() {
+    let resp = get("Message.Get").await.expect("failed to dispatch method");
+    assert_eq!(
+        sdk::deserialize::<Option<String>>(&resp),
+        Ok(Some("a".into()))
+    );
+    assert!(get("Message.Nope").await.is_err());
+}
@@ -0,0 +1,80 @@
+//! With owning ownership, records are generated without lifetimes and received as-is
+
+mod common;
+
+use common::sdk::{self, MessageDispatch};
+use wasmcloud::messaging::consumer::{Envelope, Header};
+
+#[derive(Default)]
+struct MessagingProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(MessagingProvider, {
+    inline: "
+        package wasmcloud:messaging
+
+        interface consumer {
+            record header {
+                subject: string,
+                reply-to: option<string>,
+            }
+
+            record envelope {
+                header: header,
+                body: list<u8>,
+            }
+
+            publish: func(msg: envelope, tags: list<string>) -> u32
+            fetch: func(subject: string) -> option<envelope>
+        }
+
+        world messaging {
+            import consumer
+        }
+    ",
+    ownership: Owning,
+    sdk_path: crate::common::sdk,
+});
+
+impl MessagingProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn publish(&self, _ctx: sdk::Context, msg: Envelope, tags: Vec<String>) -> u32 {
+        (msg.header.subject.len() + msg.body.len() + tags.len()) as u32
+    }
+
+    async fn fetch(&self, _ctx: sdk::Context, subject: String) -> Option<Envelope> {
+        Some(Envelope {
+            header: Header {
+                subject,
+                reply_to: None,
+            },
+            body: vec![],
+        })
+    }
+}
+
+#[tokio::test]
+async fn record_argument_is_received_as_generated() {
+    let body = sdk::serialize(&MessagingConsumerPublishInvocation {
+        msg: Envelope {
+            header: Header {
+                subject: "events".into(),
+                reply_to: Some("inbox".into()),
+            },
+            body: vec![1, 2, 3],
+        },
+        tags: vec!["a".into(), "b".into()],
+    })
+    .expect("failed to serialize invocation");
+    let resp = MessagingProvider
+        .dispatch(common::ctx("actor"), "Message.Publish".into(), body.into())
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(sdk::deserialize::<u32>(&resp), Ok(11));
+}
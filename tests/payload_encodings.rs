@@ -0,0 +1,108 @@
+//! Invocations are decoded from both positional (array) and named (map) encodings of their arguments
+
+mod common;
+
+use common::sdk::{self, MessageDispatch};
+
+#[derive(Default)]
+struct SettingsProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(SettingsProvider, {
+    world: "settings",
+    path: "tests/fixtures/settings",
+    sdk_path: crate::common::sdk,
+});
+
+impl SettingsProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn apply(&self, _ctx: sdk::Context, config: wasmcloud::settings::store::Config) -> u32 {
+        config.limits.connections * config.limits.timeout
+    }
+}
+
+/// Provider exchanging JSON, rather than the SDK's format
+mod json {
+    use crate::common::sdk;
+
+    #[derive(Default)]
+    pub struct JsonSettingsProvider;
+
+    wit_bindgen_wasmcloud_provider_guest::generate!(JsonSettingsProvider, {
+        world: "settings",
+        path: "tests/fixtures/settings",
+        sdk_path: crate::common::sdk,
+        wire_format: "json",
+    });
+
+    impl JsonSettingsProvider {
+        async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+            true
+        }
+
+        async fn _delete_link(&self, _actor_id: &str) {}
+
+        async fn _shutdown(&self) {}
+
+        async fn apply(
+            &self,
+            _ctx: sdk::Context,
+            config: wasmcloud::settings::store::Config,
+        ) -> u32 {
+            config.limits.connections * config.limits.timeout
+        }
+    }
+}
+
+/// The same logical invocation of `apply`, with its arguments (and records) named
+fn named_invocation() -> serde_json::Value {
+    serde_json::json!({
+        "config": {
+            "name": "primary",
+            "limits": { "connections": 4, "timeout": 30 },
+        },
+    })
+}
+
+/// The same logical invocation of `apply`, with its arguments (and records) positional
+fn positional_invocation() -> serde_json::Value {
+    serde_json::json!([["primary", [4, 30]]])
+}
+
+async fn apply(body: Vec<u8>) -> u32 {
+    let resp = SettingsProvider
+        .dispatch(common::ctx("actor"), "Message.Apply".into(), body.into())
+        .await
+        .expect("failed to dispatch");
+    sdk::deserialize(&resp).expect("failed to deserialize response")
+}
+
+async fn apply_json(body: Vec<u8>) -> u32 {
+    let resp = json::JsonSettingsProvider
+        .dispatch(common::ctx("actor"), "Message.Apply".into(), body.into())
+        .await
+        .expect("failed to dispatch");
+    serde_json::from_slice(&resp).expect("failed to deserialize response")
+}
+
+#[tokio::test]
+async fn msgpack_map_and_array_encodings_decode() {
+    let named = rmp_serde::to_vec_named(&named_invocation()).expect("failed to serialize");
+    let positional = rmp_serde::to_vec(&positional_invocation()).expect("failed to serialize");
+    assert_eq!(apply(named).await, 120);
+    assert_eq!(apply(positional).await, 120);
+}
+
+#[tokio::test]
+async fn json_object_and_array_encodings_decode() {
+    let named = serde_json::to_vec(&named_invocation()).expect("failed to serialize");
+    let positional = serde_json::to_vec(&positional_invocation()).expect("failed to serialize");
+    assert_eq!(apply_json(named).await, 120);
+    assert_eq!(apply_json(positional).await, 120);
+}
@@ -0,0 +1,124 @@
+//! Providers may be referred to by a path (ex. when kept in a submodule), rather than just by name
+
+mod common;
+
+use common::sdk::{self, MessageDispatch};
+use std::borrow::Cow;
+
+/// Providers kept apart from the generated code, whose methods are visible to it
+mod providers {
+    use crate::common::sdk;
+
+    /// Provider that the generated code refers to by a relative path
+    #[derive(Default)]
+    pub struct RelativeProvider;
+
+    /// Provider that the generated code refers to by an absolute path
+    #[derive(Default)]
+    pub struct AbsoluteProvider;
+
+    macro_rules! impl_kv_methods {
+        ($provider:ident, $metadata:ty, $prefix:literal) => {
+            impl $provider {
+                pub(crate) async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+                    true
+                }
+
+                pub(crate) async fn _delete_link(&self, _actor_id: &str) {}
+
+                pub(crate) async fn _shutdown(&self) {}
+
+                pub(crate) async fn get(
+                    &self,
+                    _ctx: sdk::Context,
+                    key: String,
+                ) -> Result<Option<String>, String> {
+                    Ok(Some(format!("{}{key}", $prefix)))
+                }
+
+                pub(crate) async fn set(
+                    &self,
+                    _ctx: sdk::Context,
+                    _key: String,
+                    _value: String,
+                    _metadata: $metadata,
+                ) -> Result<(), String> {
+                    Ok(())
+                }
+
+                pub(crate) async fn count(&self, _ctx: sdk::Context) -> u64 {
+                    0
+                }
+
+                pub(crate) async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+            }
+        };
+    }
+
+    impl_kv_methods!(
+        RelativeProvider,
+        crate::wasmcloud::keyvalue::store::Metadata,
+        "relative:"
+    );
+    impl_kv_methods!(
+        AbsoluteProvider,
+        crate::absolute::wasmcloud::keyvalue::store::Metadata,
+        "absolute:"
+    );
+}
+
+wit_bindgen_wasmcloud_provider_guest::generate!(providers::RelativeProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+});
+
+/// Generated code in a module of its own, referring to the provider from the crate root
+mod absolute {
+    wit_bindgen_wasmcloud_provider_guest::generate!(crate::providers::AbsoluteProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+        sdk_path: crate::common::sdk,
+    });
+
+    /// Body of an invocation of `get`
+    pub fn get_body(key: &str) -> Vec<u8> {
+        crate::common::sdk::serialize(&KeyvalueStoreGetInvocation { key: key.into() })
+            .expect("failed to serialize")
+    }
+}
+
+#[tokio::test]
+async fn providers_at_relative_paths_dispatch() {
+    let body = sdk::serialize(&KeyvalueStoreGetInvocation { key: "a".into() })
+        .expect("failed to serialize");
+    let resp = providers::RelativeProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Get".into(),
+            Cow::Borrowed(&body),
+        )
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(
+        sdk::deserialize::<Option<String>>(&resp),
+        Ok(Some("relative:a".into()))
+    );
+}
+
+#[tokio::test]
+async fn providers_at_absolute_paths_dispatch() {
+    let body = absolute::get_body("a");
+    let resp = providers::AbsoluteProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Get".into(),
+            Cow::Borrowed(&body),
+        )
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(
+        sdk::deserialize::<Option<String>>(&resp),
+        Ok(Some("absolute:a".into()))
+    );
+}
@@ -0,0 +1,100 @@
+//! WIT `result`s may omit their ok type (`result<_, E>`), or both of their types (bare `result`)
+
+mod common;
+
+use common::sdk::{self, error::ProviderInvocationError, MessageDispatch};
+use std::borrow::Cow;
+
+#[derive(Default)]
+struct JobsProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(JobsProvider, {
+    inline: "
+        package wasmcloud:jobs
+
+        interface runner {
+            ping: func(healthy: bool) -> result
+            cancel: func(id: string) -> result<_, string>
+        }
+
+        world jobs {
+            import runner
+        }
+    ",
+    sdk_path: crate::common::sdk,
+    method_skeleton: true,
+});
+
+impl JobsProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn ping(&self, _ctx: sdk::Context, healthy: bool) -> Result<(), ()> {
+        healthy.then_some(()).ok_or(())
+    }
+
+    async fn cancel(&self, _ctx: sdk::Context, id: String) -> Result<(), String> {
+        match id.as_str() {
+            "done" => Err(format!("job {id} already finished")),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Dispatch an invocation of the given method
+async fn dispatch(method: &str, body: Vec<u8>) -> Result<Vec<u8>, ProviderInvocationError> {
+    JobsProvider
+        .dispatch(common::ctx("actor"), method.into(), Cow::Owned(body))
+        .await
+}
+
+#[tokio::test]
+async fn bare_results_succeed_with_unit() {
+    let body = sdk::serialize(&JobsRunnerPingInvocation { healthy: true }).unwrap();
+    let resp = dispatch("Message.Ping", body)
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(sdk::deserialize::<()>(&resp), Ok(()));
+}
+
+#[tokio::test]
+async fn bare_results_fail_with_the_method_name() {
+    let body = sdk::serialize(&JobsRunnerPingInvocation { healthy: false }).unwrap();
+    match dispatch("Message.Ping", body).await {
+        Err(ProviderInvocationError::Provider(e)) => assert_eq!(e, "Message.Ping failed"),
+        other => panic!("expected a provider error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn results_without_ok_types_succeed_with_unit() {
+    let body = sdk::serialize(&JobsRunnerCancelInvocation {
+        id: "queued".into(),
+    })
+    .unwrap();
+    let resp = dispatch("Message.Cancel", body)
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(sdk::deserialize::<()>(&resp), Ok(()));
+}
+
+#[tokio::test]
+async fn results_without_ok_types_fail_with_their_error() {
+    let body = sdk::serialize(&JobsRunnerCancelInvocation { id: "done".into() }).unwrap();
+    match dispatch("Message.Cancel", body).await {
+        Err(ProviderInvocationError::Provider(e)) => assert_eq!(e, "job done already finished"),
+        other => panic!("expected a provider error, got {other:?}"),
+    }
+}
+
+#[test]
+fn results_without_types_are_rendered_in_signatures() {
+    assert!(PROVIDER_METHOD_SKELETON.contains("healthy: bool) -> Result<(), ()> {"));
+    assert!(PROVIDER_METHOD_SKELETON
+        .contains("id: String) -> Result<(), wit_bindgen::rt::string::String> {"));
+}
@@ -0,0 +1,100 @@
+//! String arguments are received as the type given by `string_type`
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use common::sdk::{self, MessageDispatch};
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider {
+    values: Mutex<HashMap<Box<str>, Box<str>>>,
+}
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+    string_type: Box<str>,
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: Box<str>) -> Result<Option<String>, String> {
+        Ok(self
+            .values
+            .lock()
+            .unwrap()
+            .get(&key)
+            .map(|v| v.to_string()))
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        key: Box<str>,
+        value: Box<str>,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        self.values.lock().unwrap().insert(key, value);
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        self.values.lock().unwrap().len() as u64
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, key: Box<str>) {
+        self.values.lock().unwrap().remove(&key);
+    }
+}
+
+#[test]
+fn invocation_struct_uses_string_type() {
+    let invocation = KeyvalueStoreGetInvocation {
+        key: Box::from("a"),
+    };
+    let body = sdk::serialize(&invocation).expect("failed to serialize");
+    let decoded: KeyvalueStoreGetInvocation = sdk::deserialize(&body).expect("failed to deserialize");
+    assert_eq!(decoded.key, invocation.key);
+}
+
+#[tokio::test]
+async fn string_arguments_round_trip() {
+    let provider = KvProvider::default();
+    let body = sdk::serialize(&KeyvalueStoreSetInvocation {
+        key: Box::from("a"),
+        value: Box::from("1"),
+        metadata: Metadata {
+            content_type: "text/plain".into(),
+            max_age: None,
+        },
+    })
+    .expect("failed to serialize");
+    provider
+        .dispatch(common::ctx("actor"), "Message.Set".into(), body.into())
+        .await
+        .expect("failed to set value");
+
+    let body = sdk::serialize(&KeyvalueStoreGetInvocation {
+        key: Box::from("a"),
+    })
+    .expect("failed to serialize");
+    let resp = provider
+        .dispatch(common::ctx("actor"), "Message.Get".into(), body.into())
+        .await
+        .expect("failed to get value");
+    assert_eq!(
+        sdk::deserialize::<Option<String>>(&resp),
+        Ok(Some("1".into()))
+    );
+}
@@ -0,0 +1,142 @@
+//! Providers report the operations they handle with their `SUPPORTED_OPERATIONS` associated const, which is
+//! available outside of the module `generate!` is invoked in
+
+mod common;
+
+/// Provider generated with dispatch
+mod dispatching {
+    use crate::common::sdk;
+    use wasmcloud::keyvalue::store::Metadata;
+
+    #[derive(Default)]
+    pub struct KvProvider;
+
+    wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+        sdk_path: crate::common::sdk,
+    });
+
+    /// Names of the operations generated for the provider
+    pub fn generated_operations() -> &'static [&'static str] {
+        Operation::NAMES
+    }
+
+    impl KvProvider {
+        async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+            true
+        }
+
+        async fn _delete_link(&self, _actor_id: &str) {}
+
+        async fn _shutdown(&self) {}
+
+        async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+            Ok(Some(key))
+        }
+
+        async fn set(
+            &self,
+            _ctx: sdk::Context,
+            _key: String,
+            _value: String,
+            _metadata: Metadata,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+
+        async fn count(&self, _ctx: sdk::Context) -> u64 {
+            0
+        }
+
+        async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+    }
+}
+
+/// Provider that implements `MessageDispatch` itself
+mod custom {
+    use crate::common::sdk;
+    use std::borrow::Cow;
+    use wasmcloud::keyvalue::store::Metadata;
+
+    #[derive(Default)]
+    pub struct KvProvider;
+
+    wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+        sdk_path: crate::common::sdk,
+        dispatch: false,
+    });
+
+    #[sdk::async_trait]
+    impl sdk::MessageDispatch for KvProvider {
+        async fn dispatch<'a>(
+            &'a self,
+            _ctx: sdk::Context,
+            method: String,
+            _body: Cow<'a, [u8]>,
+        ) -> Result<Vec<u8>, sdk::error::ProviderInvocationError> {
+            Err(sdk::error::ProviderInvocationError::Provider(format!(
+                "unhandled method {method}"
+            )))
+        }
+    }
+
+    impl KvProvider {
+        async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+            true
+        }
+
+        async fn _delete_link(&self, _actor_id: &str) {}
+
+        async fn _shutdown(&self) {}
+
+        async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+            Ok(Some(key))
+        }
+
+        async fn set(
+            &self,
+            _ctx: sdk::Context,
+            _key: String,
+            _value: String,
+            _metadata: Metadata,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+
+        async fn count(&self, _ctx: sdk::Context) -> u64 {
+            0
+        }
+
+        async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+    }
+}
+
+#[test]
+fn every_operation_is_reported() {
+    let mut operations = dispatching::KvProvider::SUPPORTED_OPERATIONS.to_vec();
+    operations.sort();
+    assert_eq!(
+        operations,
+        [
+            "Message.Count",
+            "Message.Delete",
+            "Message.Get",
+            "Message.Set"
+        ]
+    );
+    assert_eq!(
+        dispatching::KvProvider::SUPPORTED_OPERATIONS,
+        dispatching::generated_operations()
+    );
+}
+
+#[test]
+fn operations_are_reported_without_dispatch() {
+    assert_eq!(
+        custom::KvProvider::SUPPORTED_OPERATIONS,
+        dispatching::KvProvider::SUPPORTED_OPERATIONS
+    );
+}
@@ -0,0 +1,121 @@
+//! Providers generated with `mode: "sync"` implement blocking methods, including their lifecycle methods
+
+mod common;
+
+use common::sdk::{self, MessageDispatch, ProviderHandler};
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider {
+    links: AtomicUsize,
+}
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+    mode: "sync",
+    method_skeleton: true,
+});
+
+impl KvProvider {
+    fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        self.links.fetch_add(1, Ordering::SeqCst);
+        true
+    }
+
+    fn _delete_link(&self, _actor_id: &str) {
+        self.links.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    fn _shutdown(&self) {
+        self.links.store(0, Ordering::SeqCst);
+    }
+
+    fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+        Ok(Some(key))
+    }
+
+    fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn count(&self, _ctx: sdk::Context) -> u64 {
+        self.links.load(Ordering::SeqCst) as u64
+    }
+
+    fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+#[tokio::test]
+async fn blocking_lifecycle_methods_are_called() {
+    let provider = KvProvider::default();
+    assert!(provider.put_link(&Default::default()).await);
+    assert!(provider.put_link(&Default::default()).await);
+    provider.delete_link("actor").await;
+
+    let resp = provider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Count".into(),
+            Cow::Borrowed(&[]),
+        )
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(sdk::deserialize::<u64>(&resp), Ok(1));
+
+    provider.shutdown().await;
+    assert_eq!(provider.links.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn skeleton_has_blocking_lifecycle_methods() {
+    assert!(
+        !PROVIDER_METHOD_SKELETON.contains("async"),
+        "{PROVIDER_METHOD_SKELETON}"
+    );
+    for method in [
+        "fn _put_link(",
+        "fn _delete_link(",
+        "fn _shutdown(",
+        "fn get(",
+    ] {
+        assert!(
+            PROVIDER_METHOD_SKELETON.contains(method),
+            "{PROVIDER_METHOD_SKELETON}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn blocking_methods_are_dispatched() {
+    let body = sdk::serialize(&KeyvalueStoreGetInvocation { key: "a".into() })
+        .expect("failed to serialize invocation");
+    let resp = KvProvider::default()
+        .dispatch(common::ctx("actor"), "Message.Get".into(), body.into())
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(
+        sdk::deserialize::<Option<String>>(&resp),
+        Ok(Some("a".into()))
+    );
+}
+
+#[test]
+fn traits_have_blocking_methods() {
+    // Callable without an async runtime
+    let provider = KvProvider::default();
+    assert_eq!(
+        Store::get(&provider, common::ctx("actor"), "a".into()),
+        Ok(Some("a".into()))
+    );
+    assert_eq!(Store::count(&provider, common::ctx("actor")), 0);
+}
@@ -0,0 +1,65 @@
+//! Providers generated with `trait_suffix` have interface traits that don't clash with those of wit-bindgen
+
+mod common;
+
+use common::sdk;
+use exports::wasmcloud::mirror::store::Store;
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "mirror",
+    path: "tests/fixtures/mirror",
+    sdk_path: crate::common::sdk,
+    trait_suffix: "Provider",
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+        Ok(Some(key))
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        0
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+/// The trait wit-bindgen generates for the exported interface
+struct Mirror;
+
+impl Store for Mirror {
+    fn get(key: String) -> Option<String> {
+        Some(key)
+    }
+}
+
+#[tokio::test]
+async fn both_traits_are_in_scope() {
+    assert_eq!(
+        StoreProvider::get(&KvProvider, common::ctx("actor"), "key".into()).await,
+        Ok(Some("key".into()))
+    );
+    assert_eq!(<Mirror as Store>::get("key".into()), Some("key".into()));
+}
@@ -0,0 +1,180 @@
+//! Interface traits generated with `trait_style: "trait_variant"` return `Send` futures
+
+mod common;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+
+use common::sdk::{self, MessageDispatch};
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider {
+    values: Mutex<HashMap<String, String>>,
+}
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+    trait_style: "trait_variant",
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+        Ok(self.values.lock().unwrap().get(&key).cloned())
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        key: String,
+        value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        self.values.lock().unwrap().insert(key, value);
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        self.values.lock().unwrap().len() as u64
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, key: String) {
+        self.values.lock().unwrap().remove(&key);
+    }
+}
+
+fn assert_send<F: Future + Send>(f: F) -> F {
+    f
+}
+
+/// Methods are callable through the `Send`-bounded trait from generic code
+async fn count_entries<S: Store + Sync>(store: &S) -> u64 {
+    assert_send(store.count(common::ctx("actor"))).await
+}
+
+#[tokio::test]
+async fn trait_methods_return_send_futures() {
+    let provider = KvProvider::default();
+    let metadata = Metadata {
+        content_type: "text/plain".into(),
+        max_age: None,
+    };
+    assert_send(Store::set(
+        &provider,
+        common::ctx("actor"),
+        "a".into(),
+        "1".into(),
+        metadata,
+    ))
+    .await
+    .expect("failed to set value");
+    assert_eq!(count_entries(&provider).await, 1);
+
+    // The local variant is implemented as well
+    assert_eq!(
+        LocalStore::get(&provider, common::ctx("actor"), "a".into()).await,
+        Ok(Some("1".into()))
+    );
+}
+
+#[tokio::test]
+async fn dispatch_future_can_be_spawned() {
+    let provider = std::sync::Arc::new(KvProvider::default());
+    let body = sdk::serialize(&KeyvalueStoreCountInvocation {}).expect("failed to serialize");
+    let resp = tokio::spawn(async move {
+        provider
+            .dispatch(common::ctx("actor"), "Message.Count".into(), body.into())
+            .await
+    })
+    .await
+    .expect("dispatch panicked")
+    .expect("failed to dispatch");
+    assert_eq!(sdk::deserialize::<u64>(&resp), Ok(0));
+}
+
+/// Generic providers implement the provider's methods, rather than those of the generated traits
+/// (including the local variants) that the generic bounds bring into scope
+mod generic {
+    use std::collections::HashMap;
+    use std::marker::PhantomData;
+    use std::sync::Mutex;
+
+    use crate::common::{self, sdk};
+    use sdk::MessageDispatch;
+    use wasmcloud::keyvalue::store::Metadata;
+
+    /// Marker for the kind of values a provider stores
+    pub trait Kind: Send + Sync + 'static {}
+
+    impl Kind for String {}
+
+    pub struct KvProvider<K> {
+        values: Mutex<HashMap<String, String>>,
+        kind: PhantomData<K>,
+    }
+
+    wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider<K: Kind>, {
+        world: "keyvalue",
+        path: "tests/fixtures/keyvalue",
+        sdk_path: crate::common::sdk,
+        trait_style: "trait_variant",
+    });
+
+    impl<K: Kind> KvProvider<K> {
+        async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+            true
+        }
+
+        async fn _delete_link(&self, _actor_id: &str) {}
+
+        async fn _shutdown(&self) {}
+
+        async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+            Ok(self.values.lock().unwrap().get(&key).cloned())
+        }
+
+        async fn set(
+            &self,
+            _ctx: sdk::Context,
+            key: String,
+            value: String,
+            _metadata: Metadata,
+        ) -> Result<(), String> {
+            self.values.lock().unwrap().insert(key, value);
+            Ok(())
+        }
+
+        async fn count(&self, _ctx: sdk::Context) -> u64 {
+            self.values.lock().unwrap().len() as u64
+        }
+
+        async fn delete(&self, _ctx: sdk::Context, key: String) {
+            self.values.lock().unwrap().remove(&key);
+        }
+    }
+
+    #[tokio::test]
+    async fn generic_provider_dispatches_to_its_methods() {
+        let provider = KvProvider::<String> {
+            values: Mutex::new(HashMap::from([("a".into(), "1".into())])),
+            kind: PhantomData,
+        };
+        let body = sdk::serialize(&KeyvalueStoreCountInvocation {}).expect("failed to serialize");
+        let resp = provider
+            .dispatch(common::ctx("actor"), "Message.Count".into(), body.into())
+            .await
+            .expect("failed to dispatch");
+        assert_eq!(sdk::deserialize::<u64>(&resp), Ok(1));
+    }
+}
@@ -0,0 +1,72 @@
+//! Generated code refers to other crates by their full paths, so it builds in modules that import the same names
+
+mod common;
+
+use async_trait::async_trait;
+use common::sdk;
+use serde::{Deserialize, Serialize};
+use wasmcloud::keyvalue::store::Metadata;
+
+#[derive(Default)]
+struct KvProvider;
+
+wit_bindgen_wasmcloud_provider_guest::generate!(KvProvider, {
+    world: "keyvalue",
+    path: "tests/fixtures/keyvalue",
+    sdk_path: crate::common::sdk,
+});
+
+impl KvProvider {
+    async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+        true
+    }
+
+    async fn _delete_link(&self, _actor_id: &str) {}
+
+    async fn _shutdown(&self) {}
+
+    async fn get(&self, _ctx: sdk::Context, key: String) -> Result<Option<String>, String> {
+        Ok(Some(key))
+    }
+
+    async fn set(
+        &self,
+        _ctx: sdk::Context,
+        _key: String,
+        _value: String,
+        _metadata: Metadata,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn count(&self, _ctx: sdk::Context) -> u64 {
+        0
+    }
+
+    async fn delete(&self, _ctx: sdk::Context, _key: String) {}
+}
+
+/// A type of the provider's own, using the names it imported
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Entry {
+    key: String,
+}
+
+#[async_trait]
+trait Lookup {
+    async fn lookup(&self, key: String) -> Entry;
+}
+
+#[async_trait]
+impl Lookup for KvProvider {
+    async fn lookup(&self, key: String) -> Entry {
+        Entry { key }
+    }
+}
+
+#[tokio::test]
+async fn user_imports_coexist_with_generated_code() {
+    let entry = KvProvider.lookup("key".into()).await;
+    let body = sdk::serialize(&entry).expect("failed to serialize entry");
+    assert_eq!(sdk::deserialize::<Entry>(&body), Ok(entry));
+}
@@ -0,0 +1,187 @@
+//! Peers built against different versions of a WIT can still invoke each other: optional arguments and fields
+//! added since are defaulted when missing, and ones that are not known are ignored (unless denied)
+
+mod common;
+
+use common::sdk::{self, MessageDispatch};
+use std::borrow::Cow;
+
+/// Generate a provider for a version of the `cache` interface, in a module of its own
+macro_rules! cache_provider {
+    ($module:ident, $wit:literal $(, $opt:ident: $value:expr)* $(; $($item:item)*)?) => {
+        mod $module {
+            use crate::common::sdk;
+
+            #[derive(Default)]
+            pub struct CacheProvider;
+
+            wit_bindgen_wasmcloud_provider_guest::generate!(CacheProvider, {
+                inline: $wit,
+                sdk_path: crate::common::sdk,
+                $($opt: $value,)*
+            });
+
+            impl CacheProvider {
+                async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+                    true
+                }
+
+                async fn _delete_link(&self, _actor_id: &str) {}
+
+                async fn _shutdown(&self) {}
+            }
+
+            $($($item)*)?
+        }
+    };
+}
+
+cache_provider!(
+    v1,
+    "
+    package wasmcloud:cache
+
+    interface store {
+        record entry {
+            key: string,
+            value: string,
+        }
+
+        put: func(entry: entry) -> string
+    }
+
+    world cache {
+        import store
+    }
+    ";
+    /// Body of a `put` sent by a peer built against the first version of the WIT
+    pub fn put_body() -> Vec<u8> {
+        sdk::serialize(&CacheStorePutInvocation {
+            entry: wasmcloud::cache::store::Entry {
+                key: "a".into(),
+                value: "1".into(),
+            },
+        })
+        .expect("failed to serialize invocation")
+    }
+);
+
+cache_provider!(
+    v2,
+    "
+    package wasmcloud:cache
+
+    interface store {
+        record entry {
+            key: string,
+            value: string,
+            expires: option<u64>,
+        }
+
+        put: func(entry: entry, ttl: option<u32>) -> string
+    }
+
+    world cache {
+        import store
+    }
+    ";
+    /// Body of a `put` sent by a peer built against the second version of the WIT
+    pub fn put_body() -> Vec<u8> {
+        sdk::serialize(&CacheStorePutInvocation {
+            entry: wasmcloud::cache::store::Entry {
+                key: "a".into(),
+                value: "1".into(),
+                expires: Some(60),
+            },
+            ttl: Some(30),
+        })
+        .expect("failed to serialize invocation")
+    }
+);
+
+cache_provider!(
+    strict,
+    "
+    package wasmcloud:cache
+
+    interface store {
+        record entry {
+            key: string,
+            value: string,
+        }
+
+        put: func(entry: entry) -> string
+    }
+
+    world cache {
+        import store
+    }
+    ",
+    deny_unknown_fields: true
+);
+
+impl v1::CacheProvider {
+    async fn put(&self, _ctx: sdk::Context, entry: v1::wasmcloud::cache::store::Entry) -> String {
+        format!("{}={}", entry.key, entry.value)
+    }
+}
+
+impl v2::CacheProvider {
+    async fn put(
+        &self,
+        _ctx: sdk::Context,
+        entry: v2::wasmcloud::cache::store::Entry,
+        ttl: Option<u32>,
+    ) -> String {
+        format!(
+            "{}={} {:?} {:?}",
+            entry.key, entry.value, entry.expires, ttl
+        )
+    }
+}
+
+impl strict::CacheProvider {
+    async fn put(
+        &self,
+        _ctx: sdk::Context,
+        entry: strict::wasmcloud::cache::store::Entry,
+    ) -> String {
+        format!("{}={}", entry.key, entry.value)
+    }
+}
+
+/// Dispatch a `put` to the given provider, returning its response
+async fn put(
+    provider: &impl MessageDispatch,
+    body: Vec<u8>,
+) -> Result<String, sdk::error::ProviderInvocationError> {
+    let resp = provider
+        .dispatch(common::ctx("actor"), "Message.Put".into(), Cow::Owned(body))
+        .await?;
+    Ok(sdk::deserialize(&resp).expect("failed to deserialize response"))
+}
+
+#[tokio::test]
+async fn missing_optional_arguments_and_fields_are_defaulted() {
+    assert_eq!(
+        put(&v2::CacheProvider, v1::put_body()).await.unwrap(),
+        "a=1 None None"
+    );
+}
+
+#[tokio::test]
+async fn unknown_arguments_and_fields_are_ignored() {
+    assert_eq!(
+        put(&v1::CacheProvider, v2::put_body()).await.unwrap(),
+        "a=1"
+    );
+}
+
+#[tokio::test]
+async fn unknown_arguments_and_fields_can_be_denied() {
+    assert_eq!(
+        put(&strict::CacheProvider, v1::put_body()).await.unwrap(),
+        "a=1"
+    );
+    assert!(put(&strict::CacheProvider, v2::put_body()).await.is_err());
+}
@@ -0,0 +1,194 @@
+//! Fields of records and invocations are (de)serialized with their original WIT names only with `wit_field_names`
+
+mod common;
+
+use common::sdk::MessageDispatch;
+use std::borrow::Cow;
+
+/// Provider (de)serializing fields with their WIT names
+mod wit_names {
+    use crate::common::sdk;
+    use wasmcloud::profiles::users::UserProfile;
+
+    #[derive(Default)]
+    pub struct ProfilesProvider;
+
+    wit_bindgen_wasmcloud_provider_guest::generate!(ProfilesProvider, {
+        world: "profiles",
+        path: "tests/fixtures/profiles",
+        sdk_path: crate::common::sdk,
+        wire_format: "json",
+        wit_field_names: true,
+    });
+
+    impl ProfilesProvider {
+        async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+            true
+        }
+
+        async fn _delete_link(&self, _actor_id: &str) {}
+
+        async fn _shutdown(&self) {}
+
+        async fn update(
+            &self,
+            _ctx: sdk::Context,
+            user_id: String,
+            new_profile: UserProfile,
+        ) -> u32 {
+            (user_id.len() + new_profile.display_name.len()) as u32
+                + new_profile.last_login_at.unwrap_or_default() as u32
+                + new_profile.age
+        }
+    }
+
+    /// Decode an invocation of `update`, and encode it again
+    pub fn round_trip(json: serde_json::Value) -> serde_json::Value {
+        let invocation: ProfilesUsersUpdateInvocation =
+            serde_json::from_value(json).expect("failed to deserialize");
+        serde_json::to_value(invocation).expect("failed to serialize")
+    }
+
+    /// An invocation of `update`
+    pub fn update_invocation() -> impl serde::Serialize {
+        ProfilesUsersUpdateInvocation {
+            user_id: "u1".into(),
+            new_profile: UserProfile {
+                display_name: "Ada".into(),
+                last_login_at: Some(10),
+                age: 36,
+            },
+        }
+    }
+}
+
+/// Provider (de)serializing fields with their Rust names (the default)
+mod rust_names {
+    use crate::common::sdk;
+    use wasmcloud::profiles::users::UserProfile;
+
+    #[derive(Default)]
+    pub struct ProfilesProvider;
+
+    wit_bindgen_wasmcloud_provider_guest::generate!(ProfilesProvider, {
+        world: "profiles",
+        path: "tests/fixtures/profiles",
+        sdk_path: crate::common::sdk,
+        wire_format: "json",
+    });
+
+    impl ProfilesProvider {
+        async fn _put_link(&self, _ld: &sdk::core::LinkDefinition) -> bool {
+            true
+        }
+
+        async fn _delete_link(&self, _actor_id: &str) {}
+
+        async fn _shutdown(&self) {}
+
+        async fn update(
+            &self,
+            _ctx: sdk::Context,
+            user_id: String,
+            new_profile: UserProfile,
+        ) -> u32 {
+            (user_id.len() + new_profile.display_name.len()) as u32 + new_profile.age
+        }
+    }
+
+    /// An invocation of `update`
+    pub fn update_invocation() -> impl serde::Serialize {
+        ProfilesUsersUpdateInvocation {
+            user_id: "u1".into(),
+            new_profile: UserProfile {
+                display_name: "Ada".into(),
+                last_login_at: None,
+                age: 36,
+            },
+        }
+    }
+}
+
+#[test]
+fn fields_are_serialized_with_wit_names() {
+    let json = serde_json::to_value(wit_names::update_invocation()).expect("failed to serialize");
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "user-id": "u1",
+            "new-profile": {
+                "display-name": "Ada",
+                "last-login-at": 10,
+                "age": 36,
+            },
+        })
+    );
+
+    assert_eq!(wit_names::round_trip(json.clone()), json);
+}
+
+#[test]
+fn fields_are_serialized_with_rust_names_by_default() {
+    let json = serde_json::to_value(rust_names::update_invocation()).expect("failed to serialize");
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "user_id": "u1",
+            "new_profile": {
+                "display_name": "Ada",
+                "last_login_at": null,
+                "age": 36,
+            },
+        })
+    );
+}
+
+#[tokio::test]
+async fn invocations_with_wit_names_are_dispatched() {
+    let body = serde_json::to_vec(&serde_json::json!({
+        "user-id": "u1",
+        "new-profile": {
+            "display-name": "Ada",
+            "last-login-at": 10,
+            "age": 36,
+        },
+    }))
+    .expect("failed to serialize");
+    let resp = wit_names::ProfilesProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Update".into(),
+            Cow::Borrowed(&body),
+        )
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(
+        serde_json::from_slice::<u32>(&resp).ok(),
+        Some(2 + 3 + 10 + 36)
+    );
+
+    // Rust names are not recognized once fields are renamed
+    let body = serde_json::to_vec(&rust_names::update_invocation()).expect("failed to serialize");
+    let resp = wit_names::ProfilesProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Update".into(),
+            Cow::Borrowed(&body),
+        )
+        .await;
+    assert!(resp.is_err());
+}
+
+#[tokio::test]
+async fn invocations_with_rust_names_are_dispatched_by_default() {
+    let body = serde_json::to_vec(&rust_names::update_invocation()).expect("failed to serialize");
+    let resp = rust_names::ProfilesProvider
+        .dispatch(
+            common::ctx("actor"),
+            "Message.Update".into(),
+            Cow::Borrowed(&body),
+        )
+        .await
+        .expect("failed to dispatch");
+    assert_eq!(serde_json::from_slice::<u32>(&resp).ok(), Some(2 + 3 + 36));
+}